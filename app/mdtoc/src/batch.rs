@@ -0,0 +1,228 @@
+use crate::i18n::Message;
+use globset::{GlobBuilder, GlobMatcher};
+use ignore::WalkBuilder;
+use mdtoc_core::text::display_width;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Mutex;
+use std::thread;
+
+pub const MARKDOWN_EXTENSIONS: [&str; 3] = ["md", "markdown", "mdx"];
+
+#[derive(Debug, PartialEq)]
+pub enum Outcome {
+    /// The file is stale; holds the diff in check mode.
+    Changed(Option<String>),
+    Unchanged,
+    Failed(String),
+}
+
+#[derive(Debug, Default, PartialEq)]
+pub struct Summary {
+    pub changed: usize,
+    pub unchanged: usize,
+    pub failed: usize,
+}
+
+/// Expands files, directories and glob patterns into the files to process, taking the files with
+/// one of `extensions` from directories, and skipping `.git` and everything ignored by
+/// `.gitignore`. A glob pattern that matches no file is an error, like a missing path.
+pub fn collect_files(paths: &[String], extensions: &[&str]) -> Result<Vec<PathBuf>, String> {
+    let mut files = Vec::new();
+    for path in paths {
+        if is_glob(path) {
+            // Walked paths are matched without their `./` prefix, and so is the pattern.
+            let matcher = GlobBuilder::new(path.trim_start_matches("./"))
+                .literal_separator(true)
+                .build()
+                .map_err(|err| format!("invalid glob `{path}`: {err}"))?
+                .compile_matcher();
+            let count = files.len();
+            walk(&glob_base(path), Some(&matcher), extensions, &mut files);
+            if files.len() == count {
+                return Err(format!("`{path}` matches no files"));
+            }
+        } else if Path::new(path).is_dir() {
+            walk(Path::new(path), None, extensions, &mut files);
+        } else if Path::new(path).is_file() {
+            files.push(PathBuf::from(path));
+        } else {
+            return Err(format!("`{path}` does not exist"));
+        }
+    }
+    files.sort();
+    files.dedup();
+    Ok(files)
+}
+
+/// Runs `task` on every file with one worker per available CPU, keeping the input order.
+pub fn run_parallel<F>(files: &[PathBuf], task: F) -> Vec<Outcome>
+where
+    F: Fn(&Path) -> Outcome + Sync,
+{
+    let workers = thread::available_parallelism().map_or(1, |n| n.get());
+    let next = AtomicUsize::new(0);
+    let outcomes = Mutex::new((0..files.len()).map(|_| None).collect::<Vec<_>>());
+    thread::scope(|scope| {
+        for _ in 0..workers.min(files.len()) {
+            scope.spawn(|| loop {
+                let index = next.fetch_add(1, Ordering::Relaxed);
+                let Some(file) = files.get(index) else {
+                    break;
+                };
+                let outcome = task(file);
+                outcomes.lock().unwrap()[index] = Some(outcome);
+            });
+        }
+    });
+    outcomes
+        .into_inner()
+        .unwrap()
+        .into_iter()
+        .map(|outcome| outcome.unwrap_or_else(|| Outcome::Failed("not processed".to_owned())))
+        .collect()
+}
+
+pub fn report(files: &[PathBuf], outcomes: &[Outcome]) -> Summary {
+    let mut summary = Summary::default();
+    for (file, outcome) in files.iter().zip(outcomes) {
+        match outcome {
+            Outcome::Changed(_) => summary.changed += 1,
+            Outcome::Unchanged => summary.unchanged += 1,
+            Outcome::Failed(_) => summary.failed += 1,
+        }
+        print_outcome(file, outcome);
+    }
+    println!(
+        "{}",
+        Message::Summary {
+            changed: summary.changed,
+            unchanged: summary.unchanged,
+            failed: summary.failed,
+        }
+    );
+    summary
+}
+
+/// Prints the outcome of a file, after its diff in check mode.
+pub fn print_outcome(file: &Path, outcome: &Outcome) {
+    let labels = [Message::Changed, Message::Unchanged, Message::Failed].map(|m| m.to_string());
+    let width = labels
+        .iter()
+        .map(|label| display_width(label))
+        .max()
+        .unwrap_or(0)
+        + 2;
+    let label = |index: usize| {
+        let label = &labels[index];
+        format!("{label}{}", " ".repeat(width - display_width(label)))
+    };
+    match outcome {
+        Outcome::Changed(diff) => {
+            if let Some(diff) = diff {
+                print!("{diff}");
+            }
+            println!("{}{}", label(0), file.display());
+        }
+        Outcome::Unchanged => println!("{}{}", label(1), file.display()),
+        Outcome::Failed(err) => println!("{}{}: {err}", label(2), file.display()),
+    }
+}
+
+fn walk(root: &Path, matcher: Option<&GlobMatcher>, extensions: &[&str], files: &mut Vec<PathBuf>) {
+    let walker = WalkBuilder::new(root)
+        .hidden(false)
+        .require_git(false)
+        .filter_entry(|entry| entry.file_name() != ".git")
+        .build();
+    for entry in walker.flatten() {
+        let path = entry.path();
+        if !entry
+            .file_type()
+            .is_some_and(|file_type| file_type.is_file())
+        {
+            continue;
+        }
+        let matched = match matcher {
+            Some(matcher) => matcher.is_match(path.strip_prefix("./").unwrap_or(path)),
+            None => path
+                .extension()
+                .and_then(|extension| extension.to_str())
+                .is_some_and(|extension| extensions.contains(&extension)),
+        };
+        if matched {
+            files.push(path.to_path_buf());
+        }
+    }
+}
+
+fn is_glob(path: &str) -> bool {
+    path.contains(['*', '?', '[', '{'])
+}
+
+/// Returns the directory before the first component containing glob syntax.
+fn glob_base(pattern: &str) -> PathBuf {
+    let base: PathBuf = Path::new(pattern)
+        .components()
+        .take_while(|component| !is_glob(&component.as_os_str().to_string_lossy()))
+        .collect();
+    if base.as_os_str().is_empty() {
+        PathBuf::from(".")
+    } else {
+        base
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+
+    #[test]
+    fn test_collect_files() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path();
+        for file in [
+            "README.md",
+            "docs/a.md",
+            "docs/b.markdown",
+            "docs/c.txt",
+            "docs/d.rst",
+            "target/d.md",
+            ".git/e.md",
+        ] {
+            fs::create_dir_all(root.join(file).parent().unwrap()).unwrap();
+            fs::write(root.join(file), "# A").unwrap();
+        }
+        fs::write(root.join(".gitignore"), "target/\n").unwrap();
+        let root_str = root.to_str().unwrap();
+        let files = collect_files(&[root_str.to_owned()], &MARKDOWN_EXTENSIONS).unwrap();
+        let expected = vec![
+            root.join("README.md"),
+            root.join("docs/a.md"),
+            root.join("docs/b.markdown"),
+        ];
+        assert_eq!(expected, files);
+        let files = collect_files(&[format!("{root_str}/docs")], &["rst"]).unwrap();
+        assert_eq!(vec![root.join("docs/d.rst")], files);
+        let files = collect_files(&[format!("{root_str}/**/*.txt")], &MARKDOWN_EXTENSIONS).unwrap();
+        assert_eq!(vec![root.join("docs/c.txt")], files);
+        assert!(collect_files(&[format!("{root_str}/**/*.adoc")], &MARKDOWN_EXTENSIONS).is_err());
+    }
+
+    #[test]
+    fn test_run_parallel() {
+        let files: Vec<PathBuf> = ["a.md", "b.md", "c.md"].iter().map(PathBuf::from).collect();
+        let outcomes = run_parallel(&files, |file| match file.to_str() {
+            Some("a.md") => Outcome::Changed(None),
+            Some("b.md") => Outcome::Failed("bad".to_owned()),
+            _ => Outcome::Unchanged,
+        });
+        let expected = vec![
+            Outcome::Changed(None),
+            Outcome::Failed("bad".to_owned()),
+            Outcome::Unchanged,
+        ];
+        assert_eq!(expected, outcomes);
+    }
+}
@@ -0,0 +1,131 @@
+use crate::i18n::Message;
+use crate::lint::Diagnostic;
+use mdtoc_core::heading::parse_headings;
+use mdtoc_core::slug::{SlugStyle, Slugger};
+use similar::{capture_diff_slices, Algorithm, DiffOp};
+use std::path::Path;
+use std::process::Command;
+
+/// Pairs the anchors of the previous revision with those of the current one by diffing them,
+/// and reports the anchors that were replaced or removed. A removed anchor is reported at the
+/// heading that now follows it, or at the last heading.
+pub fn changed_anchors(
+    previous: &[String],
+    current: &[String],
+    slug_style: SlugStyle,
+) -> Vec<Diagnostic> {
+    let anchors = |lines: &[String]| {
+        let mut slugger = Slugger::new(slug_style);
+        parse_headings(lines)
+            .into_iter()
+            .map(|heading| (heading.anchor(&mut slugger), heading.line))
+            .unzip::<_, _, Vec<String>, Vec<usize>>()
+    };
+    let (old, _) = anchors(previous);
+    let (new, lines) = anchors(current);
+    let line_at = |index: usize| lines.get(index).or(lines.last()).map_or(1, |line| line + 1);
+    let mut diagnostics = Vec::new();
+    for op in capture_diff_slices(Algorithm::Myers, &old, &new) {
+        let (old_index, old_len, new_index, new_len) = match op {
+            DiffOp::Replace {
+                old_index,
+                old_len,
+                new_index,
+                new_len,
+            } => (old_index, old_len, new_index, new_len),
+            DiffOp::Delete {
+                old_index,
+                old_len,
+                new_index,
+            } => (old_index, old_len, new_index, 0),
+            DiffOp::Equal { .. } | DiffOp::Insert { .. } => continue,
+        };
+        for offset in 0..old_len {
+            let old = &old[old_index + offset];
+            diagnostics.push(if offset < new_len {
+                Diagnostic {
+                    line: line_at(new_index + offset),
+                    rule: "changed-anchor",
+                    message: Message::ChangedAnchor {
+                        old: old.clone(),
+                        new: new[new_index + offset].clone(),
+                    },
+                }
+            } else {
+                Diagnostic {
+                    line: line_at(new_index + new_len),
+                    rule: "removed-anchor",
+                    message: Message::RemovedAnchor(old.clone()),
+                }
+            });
+        }
+    }
+    diagnostics
+}
+
+/// Reads a file from its previous revision in the local git repository: `rev` if given, else
+/// `HEAD` if the file has uncommitted changes, else the revision before the last commit that
+/// changed it. Returns the raw bytes, which are in the encoding of the file.
+pub fn previous_revision(file: &Path, rev: Option<&str>) -> Result<Vec<u8>, String> {
+    let dir = file
+        .parent()
+        .filter(|dir| !dir.as_os_str().is_empty())
+        .unwrap_or(Path::new("."));
+    let name = file
+        .file_name()
+        .map(|name| name.to_string_lossy().into_owned())
+        .ok_or_else(|| format!("`{}` is not a file", file.display()))?;
+    let git = |args: &[&str]| {
+        let output = Command::new("git")
+            .arg("-C")
+            .arg(dir)
+            .args(args)
+            .output()
+            .map_err(|err| format!("cannot run git: {err}"))?;
+        if !output.status.success() {
+            return Err(String::from_utf8_lossy(&output.stderr).trim().to_owned());
+        }
+        Ok(output.stdout)
+    };
+    let git_text = |args: &[&str]| git(args).map(|out| String::from_utf8_lossy(&out).into_owned());
+    let rev = match rev {
+        Some(rev) => rev.to_owned(),
+        None if !git_text(&["status", "--porcelain", "--", &name])?
+            .trim()
+            .is_empty() =>
+        {
+            "HEAD".to_owned()
+        }
+        None => git_text(&["log", "-n", "2", "--format=%H", "--", &name])?
+            .lines()
+            .nth(1)
+            .ok_or_else(|| format!("`{}` has no previous revision", file.display()))?
+            .to_owned(),
+    };
+    git(&["show", &format!("{rev}:./{name}")])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::i18n::{message, Lang};
+
+    fn lines(content: &str) -> Vec<String> {
+        content.lines().map(str::to_owned).collect()
+    }
+
+    #[test]
+    fn test_changed_anchors() {
+        let previous = lines("# Intro\n## Instal\n## Usage\n## FAQ\n## Old");
+        let current = lines("# Intro\n## Install\n## Usage\n## New\n## FAQ {#faq}");
+        let changed: Vec<(usize, String)> = changed_anchors(&previous, &current, SlugStyle::GitHub)
+            .into_iter()
+            .map(|diagnostic| (diagnostic.line, message(Lang::En, &diagnostic.message)))
+            .collect();
+        let expected = vec![
+            (2, "`#instal` is now `#install`".to_owned()),
+            (5, "`#old` was removed".to_owned()),
+        ];
+        assert_eq!(expected, changed);
+    }
+}
@@ -0,0 +1,29 @@
+use similar::TextDiff;
+
+pub fn unified_diff(old: &str, new: &str, label: &str) -> Option<String> {
+    if old == new {
+        return None;
+    }
+    let diff = TextDiff::from_lines(old, new);
+    let unified = diff
+        .unified_diff()
+        .context_radius(3)
+        .header(label, label)
+        .to_string();
+    Some(unified)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_unified_diff() {
+        assert_eq!(None, unified_diff("# A\n", "# A\n", "a.md"));
+        let expected = "--- a.md\n+++ a.md\n@@ -1,2 +1,2 @@\n # A\n-## B\n+## 1 B\n";
+        assert_eq!(
+            Some(expected.to_owned()),
+            unified_diff("# A\n## B\n", "# A\n## 1 B\n", "a.md")
+        );
+    }
+}
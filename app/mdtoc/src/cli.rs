@@ -0,0 +1,849 @@
+use crate::clipboard::Backend;
+use crate::i18n::{self, Lang};
+use crate::index::IndexFormat;
+use crate::lint::LintFormat;
+use crate::project::{self, Kind};
+use crate::{Config, STDIO, VERSION};
+use clap::builder::{PossibleValuesParser, TypedValueParser};
+use clap::parser::ValueSource;
+use clap::{
+    Arg, ArgAction, ArgMatches, Args, Command as ClapCommand, CommandFactory, FromArgMatches,
+};
+use clap::{Parser, Subcommand};
+use clap_complete::Shell;
+use mdtoc_core::encoding::Encoding;
+use mdtoc_core::format::TocFormat;
+use mdtoc_core::ids::IdStyle;
+use mdtoc_core::markup::Markup;
+use mdtoc_core::order::OrderStyle;
+use mdtoc_core::slug::SlugStyle;
+use std::fmt::Debug;
+use std::path::Path;
+use std::str::FromStr;
+use toml::Value;
+
+const ORDER_STYLES: &[&str] = &["decimal", "dot", "roman", "chinese"];
+const SLUG_STYLES: &[&str] = &[
+    "github",
+    "gitlab",
+    "gitea",
+    "azure",
+    "azure-devops",
+    "typora",
+];
+const TOC_FORMATS: &[&str] = &["markdown", "md", "html", "json", "opml", "org"];
+const BACKENDS: &[&str] = &["windows", "wayland", "xclip", "xsel", "macos", "osc52"];
+const INDEX_FORMATS: &[&str] = &["markdown", "mdbook", "docsify"];
+const LINT_FORMATS: &[&str] = &["text", "json"];
+const ID_STYLES: &[&str] = &["anchor", "attribute"];
+const MARKUPS: &[&str] = &["markdown", "asciidoc", "rst", "org", "ipynb"];
+const LANGS: &[&str] = &["en", "zh-CN"];
+
+/// Options that select a subcommand when they come first, kept from the flag-based interface.
+const MODE_FLAGS: [(&str, &str); 6] = [
+    ("-i", "insert"),
+    ("--insert", "insert"),
+    ("-t", "toc"),
+    ("--toc", "toc"),
+    ("-o", "number"),
+    ("--title-order", "number"),
+];
+
+const AFTER_HELP: &str = "\
+Read from stdin when the file is `-` or stdin is piped, and write to stdout when no output
+is given. A single file argument is the input file, while several files, directories or
+glob patterns are processed in batch with `--in-place` or `--check`, skipping `.git` and
+`.gitignore`d files.
+
+Defaults are read from the nearest `.mdtoc.toml`, or `[mdtoc]` table of a `.config.toml`,
+in the directory of the input file or its ancestors. Its keys are the long options
+`toc`, `title-order`, `order-style`, `insert`, `ids`, `id-style`, `slug-style`,
+`min-level`, `max-level`, `format`, `collapsible`, `stats`, `toc-title`, `to-clip`,
+`clipboard`, `encoding`, `in-place`, `backup`, `check`, `index-format`, `index-sections`
+and `lint-format`, and each `[[overrides]]` table sets them for the files matching its
+`glob`, relative to the config file. Options given on the command line take precedence.
+
+The flags `-t`, `-o` and `-i` of earlier versions still select `toc`, `number` and
+`insert`, and `-fc` and `-tc` stand for `--from-clip` and `--to-clip`.
+
+Messages are in the language of `--lang`, or else of the `LC_ALL`, `LC_MESSAGES` or `LANG`
+environment variable.";
+
+/// Markdown TOC tool.
+#[derive(Debug, Parser)]
+#[command(
+    name = "mdtoc",
+    version = VERSION,
+    after_help = AFTER_HELP,
+    arg_required_else_help = true
+)]
+struct Cli {
+    #[command(subcommand)]
+    command: Command,
+}
+
+#[derive(Debug, Subcommand)]
+enum Command {
+    /// Generate the TOC of a markdown document.
+    #[command(short_flag = 't', long_flag = "toc")]
+    Toc(TransformArgs),
+    /// Insert order numbers into the titles.
+    #[command(short_flag = 'o', long_flag = "title-order")]
+    Number(TransformArgs),
+    /// Insert the TOC into the markdown.
+    ///
+    /// The TOC replaces the one between `<!-- toc -->` and `<!-- tocstop -->`, or goes after
+    /// the first H1 or the front matter.
+    #[command(short_flag = 'i', long_flag = "insert")]
+    Insert(TransformArgs),
+    /// Write explicit IDs onto the headings.
+    ///
+    /// Every listed heading without an explicit ID gets its current anchor as an `<a id="...">`
+    /// tag on the line before, or with `--id-style attribute` as a trailing `{#id}`, so that links
+    /// keep working when the title changes. Explicit IDs, including the `id` of HTML
+    /// headings, are kept, and the TOC links to them first.
+    Ids(TransformArgs),
+    /// Print a diff of what `-o`, `-i`, `-t` or `--ids` would change.
+    ///
+    /// Nothing is written, and the exit code is 4 if the result is stale.
+    Check(TransformArgs),
+    /// Report heading structure problems.
+    ///
+    /// Problems are skipped levels, multiple H1s, duplicate sibling titles with ambiguous
+    /// anchors, empty headings, trailing punctuation, and with `-o` numbering that differs
+    /// from the title order. They are reported as `file:line: [rule] message`, and the exit
+    /// code is 5 if any is found.
+    Lint(ReportArgs),
+    /// Report broken anchor links.
+    ///
+    /// Reports `#fragment` and relative `other.md#fragment` links whose file or anchor does
+    /// not exist, suggesting the closest anchor. Anchors are computed with `--slug-style` like
+    /// the TOC, and nothing is fetched over the network. The exit code is 5 if any is found.
+    Links(ReportArgs),
+    /// Report the statistics of each section.
+    ///
+    /// A section, its subsections included, is measured in words, counting every CJK character
+    /// as one, reading time, code blocks, images and links. Its age is that of its newest line
+    /// according to `git blame`, or `-` outside a git repository.
+    Stats(StatsArgs),
+    /// Print the section under a heading path.
+    ///
+    /// The path names nested headings separated by `>`, such as `"Install > Linux"`, and
+    /// titles are compared as plain text ignoring case. The section ends before the next
+    /// heading of the same or a higher level.
+    Extract(ExtractArgs),
+    /// Split a document into one file per heading of a level.
+    ///
+    /// Chapters are written to `--out-dir` as `01-title.md`, with their headings shifted so
+    /// that they start at H1. The index left behind, with a link to each chapter in place of
+    /// its section, goes to the output. Fragment links are rewritten to the file that holds
+    /// their anchor.
+    Split(SplitArgs),
+    /// Concatenate documents into one.
+    ///
+    /// Front matter is dropped and headings are shifted down by `--shift` levels. Fragment
+    /// links, and relative links between the merged files, are rewritten to the anchors of
+    /// the merged document.
+    Merge(MergeArgs),
+    /// Generate an index of the markdown files in a directory.
+    ///
+    /// Files are titled by their first heading, and `SUMMARY.md` and `_sidebar.md` are left
+    /// out.
+    Index(IndexArgs),
+    /// Print the shell completion script.
+    Completions {
+        #[arg(value_enum)]
+        shell: Shell,
+    },
+    /// Print the man page.
+    Man,
+}
+
+#[derive(Debug, Args)]
+struct InputArgs {
+    /// The input file, `-` for stdin, or files, directories and glob patterns to process in
+    /// batch.
+    #[arg(value_name = "PATH")]
+    paths: Vec<String>,
+    /// Read markdown content from file.
+    #[arg(short = 'f', long, value_name = "FILE")]
+    file: Option<String>,
+    /// Read markdown content from clipboard.
+    #[arg(long)]
+    from_clip: bool,
+    /// Encoding of the input and the output, such as `gbk`. UTF-8 and UTF-16 are detected.
+    #[arg(long, value_name = "ENCODING", value_parser = Encoding::from_str)]
+    encoding: Option<Encoding>,
+}
+
+#[derive(Debug, Args)]
+struct HeadingArgs {
+    /// Title order style.
+    #[arg(long, value_name = "STYLE", value_parser = choice::<OrderStyle>(ORDER_STYLES))]
+    order_style: Option<OrderStyle>,
+    /// Anchor style.
+    #[arg(long, value_name = "STYLE", value_parser = choice::<SlugStyle>(SLUG_STYLES))]
+    slug_style: Option<SlugStyle>,
+    /// Lowest heading level in TOC and title order.
+    #[arg(long, value_name = "LEVEL", value_parser = clap::value_parser!(u8).range(1..=6))]
+    min_level: Option<u8>,
+    /// Highest heading level in TOC and title order.
+    ///
+    /// Headings marked `<!-- omit in toc -->` and sections between
+    /// `<!-- toc:ignore-start -->` and `<!-- toc:ignore-end -->` are always left out. The
+    /// `toc` key of the front matter may set `depth`, `min-level`, `max-level` and `title`
+    /// per file.
+    #[arg(long, value_name = "LEVEL", value_parser = clap::value_parser!(u8).range(1..=6))]
+    max_level: Option<u8>,
+}
+
+#[derive(Debug, Args)]
+struct WriteArgs {
+    /// Write result to file, `-` for stdout.
+    #[arg(short = 'w', long, value_name = "FILE")]
+    write: Option<String>,
+    /// Write result to clipboard.
+    #[arg(long)]
+    to_clip: bool,
+    /// Clipboard backend, detected from the session by default.
+    #[arg(long, value_name = "BACKEND", value_parser = choice::<Backend>(BACKENDS))]
+    clipboard: Option<Backend>,
+}
+
+#[derive(Debug, Args)]
+struct OutputArgs {
+    #[command(flatten)]
+    target: WriteArgs,
+    /// Write result back to the input files atomically.
+    #[arg(long)]
+    in_place: bool,
+    /// Keep a `.bak` copy of the input file with `--in-place`.
+    #[arg(long)]
+    backup: bool,
+    /// Print a diff of what would change instead of writing it.
+    #[arg(long)]
+    check: bool,
+    /// Keep running, and process the files again whenever they change.
+    ///
+    /// Files are polled for changes, and processed once they have been left alone for half a
+    /// second. Files added to a watched directory are picked up.
+    #[arg(long)]
+    watch: bool,
+}
+
+#[derive(Debug, Args)]
+struct TransformArgs {
+    #[command(flatten)]
+    input: InputArgs,
+    /// Markup of the input, detected from the file extension by default.
+    #[arg(long, value_name = "MARKUP", value_parser = choice::<Markup>(MARKUPS))]
+    markup: Option<Markup>,
+    /// Generate TOC.
+    #[arg(short = 't', long)]
+    toc: bool,
+    /// Insert order number to title.
+    #[arg(short = 'o', long)]
+    title_order: bool,
+    /// Insert TOC into the markdown.
+    #[arg(short = 'i', long)]
+    insert: bool,
+    /// Write explicit IDs onto the headings.
+    #[arg(long)]
+    ids: bool,
+    /// Explicit ID syntax.
+    #[arg(long, value_name = "STYLE", value_parser = choice::<IdStyle>(ID_STYLES))]
+    id_style: Option<IdStyle>,
+    /// List the anchors that changed or were removed since the previous git revision of the
+    /// file, or `REV`.
+    ///
+    /// The previous revision is `HEAD` if the file has uncommitted changes, else the one before
+    /// the last commit that changed it. Nothing is written.
+    #[arg(
+        long,
+        value_name = "REV",
+        num_args = 0..=1,
+        require_equals = true,
+        default_missing_value = ""
+    )]
+    changed: Option<String>,
+    #[command(flatten)]
+    headings: HeadingArgs,
+    /// TOC format.
+    #[arg(long, value_name = "FORMAT", value_parser = choice::<TocFormat>(TOC_FORMATS))]
+    format: Option<TocFormat>,
+    /// Wrap the HTML TOC in a collapsible `<details>`.
+    #[arg(long)]
+    collapsible: bool,
+    /// Title of the TOC, shown above it, or in the summary of a collapsible HTML TOC.
+    #[arg(long, value_name = "TITLE")]
+    toc_title: Option<String>,
+    /// Append the word count and reading time of each section to its TOC entry.
+    #[arg(long)]
+    stats: bool,
+    #[command(flatten)]
+    output: OutputArgs,
+    /// Print the effective settings and where each one is from.
+    #[arg(long)]
+    print_config: bool,
+}
+
+#[derive(Debug, Args)]
+struct ReportArgs {
+    #[command(flatten)]
+    input: InputArgs,
+    /// Check the numbering against the title order.
+    #[arg(short = 'o', long)]
+    title_order: bool,
+    #[command(flatten)]
+    headings: HeadingArgs,
+    /// Report format.
+    #[arg(
+        long = "format",
+        visible_alias = "lint-format",
+        value_name = "FORMAT",
+        value_parser = choice::<LintFormat>(LINT_FORMATS)
+    )]
+    lint_format: Option<LintFormat>,
+    /// Print the effective settings and where each one is from.
+    #[arg(long)]
+    print_config: bool,
+}
+
+#[derive(Debug, Args)]
+struct StatsArgs {
+    #[command(flatten)]
+    input: InputArgs,
+    /// Markup of the input, detected from the file extension by default.
+    #[arg(long, value_name = "MARKUP", value_parser = choice::<Markup>(MARKUPS))]
+    markup: Option<Markup>,
+    #[command(flatten)]
+    headings: HeadingArgs,
+    /// Report format: a table per file, or JSON.
+    #[arg(
+        long = "format",
+        value_name = "FORMAT",
+        value_parser = choice::<LintFormat>(LINT_FORMATS)
+    )]
+    stats_format: Option<LintFormat>,
+    /// Print the effective settings and where each one is from.
+    #[arg(long)]
+    print_config: bool,
+}
+
+#[derive(Debug, Args)]
+struct IndexArgs {
+    /// The directory to index.
+    #[arg(value_name = "DIR")]
+    dir: String,
+    /// Index format: a markdown list, mdBook `SUMMARY.md` or docsify `_sidebar.md`.
+    #[arg(
+        long = "format",
+        visible_alias = "index-format",
+        value_name = "FORMAT",
+        value_parser = choice::<IndexFormat>(INDEX_FORMATS)
+    )]
+    index_format: Option<IndexFormat>,
+    /// Include the H2 sections of each file in the index.
+    #[arg(long = "sections", visible_alias = "index-sections")]
+    index_sections: bool,
+    /// Anchor style.
+    #[arg(long, value_name = "STYLE", value_parser = choice::<SlugStyle>(SLUG_STYLES))]
+    slug_style: Option<SlugStyle>,
+    #[command(flatten)]
+    output: OutputArgs,
+    /// Print the effective settings and where each one is from.
+    #[arg(long)]
+    print_config: bool,
+}
+
+#[derive(Debug, Args)]
+struct ExtractArgs {
+    /// The heading path, such as `"Install > Linux"`.
+    #[arg(value_name = "HEADING")]
+    heading: String,
+    #[command(flatten)]
+    input: InputArgs,
+    #[command(flatten)]
+    output: WriteArgs,
+}
+
+#[derive(Debug, Args)]
+struct SplitArgs {
+    #[command(flatten)]
+    input: InputArgs,
+    /// The level of the headings that start a chapter.
+    #[arg(
+        long,
+        value_name = "LEVEL",
+        default_value_t = 2,
+        value_parser = clap::value_parser!(u8).range(1..=6)
+    )]
+    level: u8,
+    /// The directory of the chapter files, by default that of the input file.
+    #[arg(long, value_name = "DIR")]
+    out_dir: Option<String>,
+    /// Anchor style.
+    #[arg(long, value_name = "STYLE", value_parser = choice::<SlugStyle>(SLUG_STYLES))]
+    slug_style: Option<SlugStyle>,
+    #[command(flatten)]
+    output: WriteArgs,
+    /// Replace the input file with the index.
+    #[arg(long)]
+    in_place: bool,
+    /// Keep a `.bak` copy of the input file with `--in-place`.
+    #[arg(long)]
+    backup: bool,
+}
+
+#[derive(Debug, Args)]
+struct MergeArgs {
+    /// The files to merge, in order.
+    #[arg(value_name = "FILE", required = true)]
+    files: Vec<String>,
+    /// Put the merged documents under an H1 with this title.
+    #[arg(long, value_name = "TITLE")]
+    title: Option<String>,
+    /// The number of levels to move the headings down.
+    #[arg(
+        long,
+        value_name = "LEVELS",
+        default_value_t = 1,
+        value_parser = clap::value_parser!(u8).range(0..=5)
+    )]
+    shift: u8,
+    /// Anchor style.
+    #[arg(long, value_name = "STYLE", value_parser = choice::<SlugStyle>(SLUG_STYLES))]
+    slug_style: Option<SlugStyle>,
+    #[command(flatten)]
+    output: WriteArgs,
+}
+
+pub enum Action {
+    Run(Box<Config>),
+    Completions(Shell),
+    Man,
+}
+
+/// Builds the command line, with the help in `lang`.
+pub fn command(lang: Lang) -> ClapCommand {
+    let version = Arg::new("version")
+        .short('v')
+        .long("version")
+        .action(ArgAction::Version)
+        .help("Print version");
+    let lang_arg = Arg::new("lang")
+        .long("lang")
+        .global(true)
+        .value_name("LANG")
+        .value_parser(choice::<Lang>(LANGS))
+        .help("Language of the messages, by default that of the locale.");
+    let mut command = Cli::command()
+        .disable_version_flag(true)
+        .arg(version)
+        .arg(lang_arg);
+    if lang == Lang::En {
+        return command;
+    }
+    // The help flags and the `help` subcommand are added by the build.
+    command.build();
+    localize(command, lang)
+}
+
+/// Translates the help of a built command and its subcommands, including the headings and the
+/// usage title of clap. Texts missing from the catalog are left in English.
+fn localize(command: ClapCommand, lang: Lang) -> ClapCommand {
+    let text = |key: &str| i18n::help(lang, key);
+    let heading = |name: &'static str| text(&format!("heading.{name}")).unwrap_or(name);
+    let name = command.get_name().to_owned();
+    let usage = command.get_styles().get_usage();
+    let template = format!(
+        "{{before-help}}{{about-with-newline}}\n{usage}{}:{usage:#} {{usage}}\n\n{{all-args}}{{after-help}}",
+        heading("usage")
+    );
+    let mut command = command
+        .help_template(template)
+        .subcommand_help_heading(heading("commands"))
+        .mut_args(|arg| {
+            let id = arg.get_id().as_str();
+            let help = text(&format!("arg.{name}.{id}")).or_else(|| text(&format!("arg.{id}")));
+            // Headed arguments are listed in order of their first one, so positionals come first.
+            let arg = match arg.is_positional() {
+                true => arg.help_heading(heading("arguments")),
+                false => arg.help_heading(heading("options")),
+            };
+            match help.map(paragraphs) {
+                Some((short, long)) => arg.help(short).long_help(long),
+                None => arg,
+            }
+        })
+        .mut_subcommands(|command| localize(command, lang));
+    if let Some((short, long)) = text(&format!("command.{name}")).map(paragraphs) {
+        command = command.about(short).long_about(long);
+    }
+    if let Some(after_help) = text("after-help").filter(|_| name == "mdtoc") {
+        command = command.after_help(after_help);
+    }
+    command
+}
+
+/// Splits a help text into its first paragraph, and the whole text if it has more.
+fn paragraphs(text: &'static str) -> (&'static str, Option<&'static str>) {
+    match text.split_once("\n\n") {
+        Some((first, _)) => (first, Some(text)),
+        None => (text, None),
+    }
+}
+
+/// Rewrites the arguments of the flag-based interface: `-fc` and `-tc` become long options,
+/// and the first mode flag is moved to the front when other options come before it, e.g.
+/// `mdtoc -f README.md -o -i` becomes `mdtoc -i -f README.md -o`.
+pub fn normalize(args: impl IntoIterator<Item = String>) -> Vec<String> {
+    let mut args: Vec<String> = args
+        .into_iter()
+        .map(|arg| match arg.as_str() {
+            "-fc" => "--from-clip".to_owned(),
+            "-tc" => "--to-clip".to_owned(),
+            _ => arg,
+        })
+        .collect();
+    let legacy = args.get(1).is_some_and(|first| {
+        first.starts_with('-')
+            && !["-h", "--help", "-v", "--version"].contains(&first.as_str())
+            && !MODE_FLAGS.iter().any(|(flag, _)| flag == first)
+    }) && !args[1..].iter().any(|arg| is_subcommand(arg));
+    if !legacy {
+        return args;
+    }
+    let mode = MODE_FLAGS
+        .iter()
+        .find_map(|(flag, mode)| Some((args.iter().position(|arg| arg == flag)?, *mode)));
+    if let Some((position, mode)) = mode {
+        args.remove(position);
+        args.insert(1, mode.to_owned());
+    } else if let Some(position) = args.iter().position(|arg| arg == "--index") {
+        args.remove(position);
+        args.insert(1, "index".to_owned());
+    } else {
+        // Without a mode flag, the TOC was printed.
+        args.insert(1, "toc".to_owned());
+    }
+    args
+}
+
+fn is_subcommand(arg: &str) -> bool {
+    Cli::command().get_subcommands().any(|command| {
+        command.get_name() == arg || command.get_all_aliases().any(|alias| alias == arg)
+    })
+}
+
+/// Turns the parsed arguments into the action to run, recording the settings given on the
+/// command line so that they take precedence over config files.
+pub fn action(matches: &ArgMatches) -> Result<Action, clap::Error> {
+    let mut config = Config::default();
+    match Cli::from_arg_matches(matches)?.command {
+        Command::Toc(args) => {
+            config.toc = true;
+            args.apply(&mut config);
+        }
+        Command::Number(args) => {
+            config.title_order = true;
+            args.apply(&mut config);
+        }
+        Command::Insert(args) => {
+            config.insert = true;
+            args.apply(&mut config);
+        }
+        Command::Ids(args) => {
+            config.ids = true;
+            args.apply(&mut config);
+        }
+        Command::Check(args) => {
+            config.check = true;
+            args.apply(&mut config);
+        }
+        Command::Lint(args) => {
+            config.lint = true;
+            args.apply(&mut config);
+        }
+        Command::Links(args) => {
+            config.links = true;
+            args.apply(&mut config);
+        }
+        Command::Stats(args) => {
+            config.stats = true;
+            args.apply(&mut config);
+        }
+        Command::Extract(args) => args.apply(&mut config),
+        Command::Split(args) => args.apply(&mut config),
+        Command::Merge(args) => args.apply(&mut config),
+        Command::Index(args) => args.apply(&mut config),
+        Command::Completions { shell } => return Ok(Action::Completions(shell)),
+        Command::Man => return Ok(Action::Man),
+    }
+    if let Some((name, matches)) = matches.subcommand() {
+        record_sources(&mut config, name, matches);
+    }
+    Ok(Action::Run(Box::new(config)))
+}
+
+fn record_sources(config: &mut Config, subcommand: &str, matches: &ArgMatches) {
+    let implied = match subcommand {
+        "toc" => Some("toc"),
+        "number" => Some("title-order"),
+        "insert" => Some("insert"),
+        "ids" => Some("ids"),
+        "check" => Some("check"),
+        _ => None,
+    };
+    for (key, kind, _) in project::KEYS {
+        let id = key.replace('-', "_");
+        let given = matches.ids().any(|given| given == id.as_str())
+            && matches.value_source(&id) == Some(ValueSource::CommandLine);
+        let value = if given {
+            match kind {
+                Kind::Bool => Value::Boolean(true),
+                Kind::Level => {
+                    Value::Integer(matches.get_one::<u8>(&id).map_or(0, |&level| level.into()))
+                }
+                Kind::Str => {
+                    let raw = matches.get_raw(&id).into_iter().flatten().next();
+                    Value::String(raw.unwrap_or_default().to_string_lossy().into_owned())
+                }
+            }
+        } else if implied == Some(key) {
+            Value::Boolean(true)
+        } else {
+            continue;
+        };
+        config
+            .sources
+            .insert(key, (value, project::COMMAND_LINE.to_owned()));
+    }
+}
+
+impl InputArgs {
+    fn apply(self, config: &mut Config) {
+        config.input_file = self.file;
+        config.from_clip = self.from_clip;
+        config.encoding = self.encoding;
+        config.paths = self.paths;
+        if let Some(position) = config.paths.iter().position(|path| path == STDIO) {
+            config.input_file = Some(config.paths.remove(position));
+        }
+        let single_file = config.paths.len() == 1 && Path::new(&config.paths[0]).is_file();
+        if single_file && config.input_file.is_none() && !config.from_clip {
+            config.input_file = config.paths.pop();
+        }
+    }
+}
+
+impl HeadingArgs {
+    fn apply(self, config: &mut Config) {
+        set(&mut config.order_style, self.order_style);
+        set(&mut config.slug_style, self.slug_style);
+        set(&mut config.levels.min, self.min_level);
+        set(&mut config.levels.max, self.max_level);
+    }
+}
+
+impl WriteArgs {
+    fn apply(self, config: &mut Config) {
+        config.output_file = self.write;
+        config.to_clip = self.to_clip;
+        config.clipboard = self.clipboard;
+    }
+}
+
+impl OutputArgs {
+    fn apply(self, config: &mut Config) {
+        self.target.apply(config);
+        config.in_place = self.in_place;
+        config.backup = self.backup;
+        config.check |= self.check;
+        config.watch = self.watch;
+    }
+}
+
+impl TransformArgs {
+    fn apply(self, config: &mut Config) {
+        self.input.apply(config);
+        config.markup = self.markup;
+        config.toc |= self.toc;
+        config.title_order |= self.title_order;
+        config.insert |= self.insert;
+        config.ids |= self.ids;
+        set(&mut config.id_style, self.id_style);
+        config.changed = self.changed;
+        self.headings.apply(config);
+        set(&mut config.format, self.format);
+        config.collapsible = self.collapsible;
+        config.toc_title = self.toc_title;
+        config.toc_stats |= self.stats;
+        self.output.apply(config);
+        config.print_config = self.print_config;
+    }
+}
+
+impl ReportArgs {
+    fn apply(self, config: &mut Config) {
+        self.input.apply(config);
+        config.title_order = self.title_order;
+        self.headings.apply(config);
+        set(&mut config.lint_format, self.lint_format);
+        config.print_config = self.print_config;
+    }
+}
+
+impl StatsArgs {
+    fn apply(self, config: &mut Config) {
+        self.input.apply(config);
+        config.markup = self.markup;
+        self.headings.apply(config);
+        set(&mut config.stats_format, self.stats_format);
+        config.print_config = self.print_config;
+    }
+}
+
+impl ExtractArgs {
+    fn apply(self, config: &mut Config) {
+        config.extract = Some(self.heading);
+        self.input.apply(config);
+        self.output.apply(config);
+    }
+}
+
+impl SplitArgs {
+    fn apply(self, config: &mut Config) {
+        self.input.apply(config);
+        config.split = Some(self.level);
+        config.out_dir = self.out_dir;
+        set(&mut config.slug_style, self.slug_style);
+        self.output.apply(config);
+        config.in_place = self.in_place;
+        config.backup = self.backup;
+    }
+}
+
+impl MergeArgs {
+    fn apply(self, config: &mut Config) {
+        config.merge = true;
+        config.paths = self.files;
+        config.merge_title = self.title;
+        config.shift = self.shift;
+        set(&mut config.slug_style, self.slug_style);
+        self.output.apply(config);
+    }
+}
+
+impl IndexArgs {
+    fn apply(self, config: &mut Config) {
+        config.index = Some(self.dir);
+        set(&mut config.index_format, self.index_format);
+        config.index_sections = self.index_sections;
+        set(&mut config.slug_style, self.slug_style);
+        self.output.apply(config);
+        config.print_config = self.print_config;
+    }
+}
+
+fn set<T>(field: &mut T, value: Option<T>) {
+    if let Some(value) = value {
+        *field = value;
+    }
+}
+
+/// Parses one of `values` with `FromStr`, listing the values in help and completions.
+fn choice<T>(values: &'static [&'static str]) -> impl TypedValueParser<Value = T>
+where
+    T: FromStr + Clone + Send + Sync + 'static,
+    T::Err: Debug,
+{
+    PossibleValuesParser::new(values).map(|value| value.parse().expect("a possible value"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(args: &str) -> Result<Config, clap::Error> {
+        let args = normalize(args.split_whitespace().map(str::to_owned));
+        let matches = command(Lang::En).try_get_matches_from(args)?;
+        match action(&matches)? {
+            Action::Run(config) => Ok(*config),
+            _ => panic!("expect a config"),
+        }
+    }
+
+    #[test]
+    fn test_normalize() {
+        let args = |args: &str| normalize(args.split_whitespace().map(str::to_owned)).join(" ");
+        assert_eq!("mdtoc insert -f a.md -o", args("mdtoc -f a.md -o -i"));
+        assert_eq!("mdtoc toc --from-clip --to-clip", args("mdtoc -fc -t -tc"));
+        assert_eq!(
+            "mdtoc index docs --check",
+            args("mdtoc --index docs --check")
+        );
+        assert_eq!("mdtoc -o -i a.md", args("mdtoc -o -i a.md"));
+        assert_eq!("mdtoc toc -f a.md", args("mdtoc -f a.md"));
+        assert_eq!(
+            "mdtoc --lang en lint a.md",
+            args("mdtoc --lang en lint a.md")
+        );
+        assert_eq!(
+            "mdtoc --lang en toc -i a.md",
+            args("mdtoc --lang en toc -i a.md")
+        );
+    }
+
+    #[test]
+    fn test_parse() {
+        let config = parse("mdtoc -o -i -f a.md --max-level 3 --slug-style gitlab").unwrap();
+        assert!(config.title_order && config.insert && !config.toc);
+        assert_eq!(Some("a.md".to_owned()), config.input_file);
+        assert_eq!(3, config.levels.max);
+        assert_eq!(SlugStyle::GitLab, config.slug_style);
+        let sources: Vec<&str> = config.sources.keys().copied().collect();
+        assert_eq!(
+            vec!["insert", "max-level", "slug-style", "title-order"],
+            sources
+        );
+
+        let config = parse("mdtoc lint --format json docs").unwrap();
+        assert!(config.lint);
+        assert_eq!(LintFormat::Json, config.lint_format);
+        assert_eq!(vec!["docs".to_owned()], config.paths);
+
+        let config = parse("mdtoc merge a.md b.md --title Book").unwrap();
+        assert!(config.merge);
+        assert_eq!(vec!["a.md".to_owned(), "b.md".to_owned()], config.paths);
+        assert_eq!(
+            (Some("Book".to_owned()), 1),
+            (config.merge_title, config.shift)
+        );
+        assert!(parse("mdtoc merge").is_err());
+        assert!(parse("mdtoc extract").is_err());
+    }
+
+    #[test]
+    fn test_parse_rejects_unknown_flags() {
+        let err = parse("mdtoc toc --tco -f a.md").unwrap_err();
+        assert_eq!(clap::error::ErrorKind::UnknownArgument, err.kind());
+        assert!(parse("mdtoc toc --max-level 7").is_err());
+        assert!(parse("mdtoc toc --format yaml").is_err());
+    }
+
+    #[test]
+    fn test_command() {
+        command(Lang::En).debug_assert();
+        command(Lang::ZhCn).debug_assert();
+        let mut zh = command(Lang::ZhCn);
+        let help = zh.render_long_help().to_string();
+        assert!(help.starts_with("Markdown 目录工具。\n\n用法: mdtoc"));
+        assert!(help.contains("命令:\n  toc, -t, --toc             生成 Markdown 文档的目录。"));
+        let extract = zh.find_subcommand_mut("extract").unwrap();
+        let help = extract.render_help().to_string();
+        assert!(help.contains("参数:\n  <HEADING>  标题路径"));
+        assert!(help.contains("选项:\n  -f, --file <FILE>"));
+        assert!(help.contains("  -h, --help                 打印帮助"));
+    }
+}
@@ -0,0 +1,312 @@
+use mdtoc_core::Error;
+use std::env;
+use std::fmt::{Display, Formatter};
+use std::fs::OpenOptions;
+use std::io::{self, Read, Seek, SeekFrom, Write};
+use std::path::Path;
+use std::process::{Command, Stdio};
+use std::str::FromStr;
+
+#[derive(Debug, PartialEq)]
+pub struct ClipboardError {
+    pub backend: &'static str,
+    pub msg: String,
+}
+
+impl Display for ClipboardError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "[{}] {}", self.backend, self.msg)
+    }
+}
+
+pub trait Clipboard {
+    fn name(&self) -> &'static str;
+
+    fn get(&mut self) -> Result<String, ClipboardError>;
+
+    fn set(&mut self, content: &str) -> Result<(), ClipboardError>;
+
+    fn error(&self, msg: impl Display) -> ClipboardError
+    where
+        Self: Sized,
+    {
+        ClipboardError {
+            backend: self.name(),
+            msg: msg.to_string(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Backend {
+    Windows,
+    Wayland,
+    Xclip,
+    Xsel,
+    MacOs,
+    Osc52,
+}
+
+impl FromStr for Backend {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "windows" => Ok(Backend::Windows),
+            "wayland" => Ok(Backend::Wayland),
+            "xclip" => Ok(Backend::Xclip),
+            "xsel" => Ok(Backend::Xsel),
+            "macos" => Ok(Backend::MacOs),
+            "osc52" => Ok(Backend::Osc52),
+            _ => Err(Error::UnknownValue {
+                kind: "clipboard",
+                value: s.to_owned(),
+                expected: "windows, wayland, xclip, xsel, macos, osc52",
+            }),
+        }
+    }
+}
+
+impl Backend {
+    /// Picks the first backend usable in the current session.
+    pub fn detect() -> Backend {
+        if cfg!(windows) {
+            Backend::Windows
+        } else if cfg!(target_os = "macos") {
+            Backend::MacOs
+        } else if env::var_os("WAYLAND_DISPLAY").is_some() && in_path("wl-copy") {
+            Backend::Wayland
+        } else if env::var_os("DISPLAY").is_some() && in_path("xclip") {
+            Backend::Xclip
+        } else if env::var_os("DISPLAY").is_some() && in_path("xsel") {
+            Backend::Xsel
+        } else {
+            Backend::Osc52
+        }
+    }
+
+    pub fn open(self) -> Box<dyn Clipboard> {
+        match self {
+            Backend::Windows => Box::new(WindowsClipboard),
+            Backend::Wayland => Box::new(CommandClipboard {
+                name: "wayland",
+                copy: &["wl-copy"],
+                paste: &["wl-paste", "--no-newline"],
+            }),
+            Backend::Xclip => Box::new(CommandClipboard {
+                name: "xclip",
+                copy: &["xclip", "-selection", "clipboard", "-in"],
+                paste: &["xclip", "-selection", "clipboard", "-out"],
+            }),
+            Backend::Xsel => Box::new(CommandClipboard {
+                name: "xsel",
+                copy: &["xsel", "--clipboard", "--input"],
+                paste: &["xsel", "--clipboard", "--output"],
+            }),
+            Backend::MacOs => Box::new(CommandClipboard {
+                name: "macos",
+                copy: &["pbcopy"],
+                paste: &["pbpaste"],
+            }),
+            Backend::Osc52 => Box::new(Osc52Clipboard),
+        }
+    }
+}
+
+pub struct WindowsClipboard;
+
+impl Clipboard for WindowsClipboard {
+    fn name(&self) -> &'static str {
+        "windows"
+    }
+
+    #[cfg(windows)]
+    fn get(&mut self) -> Result<String, ClipboardError> {
+        clipboard_win::get_clipboard_string().map_err(|err| self.error(err))
+    }
+
+    #[cfg(windows)]
+    fn set(&mut self, content: &str) -> Result<(), ClipboardError> {
+        clipboard_win::set_clipboard_string(content).map_err(|err| self.error(err))
+    }
+
+    #[cfg(not(windows))]
+    fn get(&mut self) -> Result<String, ClipboardError> {
+        Err(self.error("only available on Windows"))
+    }
+
+    #[cfg(not(windows))]
+    fn set(&mut self, _content: &str) -> Result<(), ClipboardError> {
+        Err(self.error("only available on Windows"))
+    }
+}
+
+/// Delegates to external tools such as `wl-copy`/`wl-paste`, `xclip` and `xsel`.
+pub struct CommandClipboard {
+    name: &'static str,
+    copy: &'static [&'static str],
+    paste: &'static [&'static str],
+}
+
+impl Clipboard for CommandClipboard {
+    fn name(&self) -> &'static str {
+        self.name
+    }
+
+    fn get(&mut self) -> Result<String, ClipboardError> {
+        let output = Command::new(self.paste[0])
+            .args(&self.paste[1..])
+            .stderr(Stdio::piped())
+            .output()
+            .map_err(|err| self.error(format!("failed to run `{}`: {err}", self.paste[0])))?;
+        if !output.status.success() {
+            return Err(self.error(String::from_utf8_lossy(&output.stderr).trim()));
+        }
+        String::from_utf8(output.stdout).map_err(|err| self.error(err))
+    }
+
+    fn set(&mut self, content: &str) -> Result<(), ClipboardError> {
+        // The tools fork a process that keeps the clipboard and inherits stderr, so stderr goes to
+        // a file rather than a pipe that would not be closed until that process exits.
+        let mut stderr = tempfile::tempfile().map_err(|err| self.error(err))?;
+        let stderr_writer = stderr.try_clone().map_err(|err| self.error(err))?;
+        let mut child = Command::new(self.copy[0])
+            .args(&self.copy[1..])
+            .stdin(Stdio::piped())
+            .stdout(Stdio::null())
+            .stderr(stderr_writer)
+            .spawn()
+            .map_err(|err| self.error(format!("failed to run `{}`: {err}", self.copy[0])))?;
+        if let Some(mut stdin) = child.stdin.take() {
+            stdin
+                .write_all(content.as_bytes())
+                .map_err(|err| self.error(err))?;
+        }
+        let status = child.wait().map_err(|err| self.error(err))?;
+        if !status.success() {
+            let mut message = String::new();
+            stderr
+                .seek(SeekFrom::Start(0))
+                .and_then(|_| stderr.read_to_string(&mut message))
+                .map_err(|err| self.error(err))?;
+            return Err(self.error(message.trim()));
+        }
+        Ok(())
+    }
+}
+
+/// Sets the clipboard of the terminal emulator through an OSC 52 escape sequence, which also
+/// works over SSH. Terminals rarely allow reading the clipboard back, so `get` is unsupported.
+pub struct Osc52Clipboard;
+
+impl Clipboard for Osc52Clipboard {
+    fn name(&self) -> &'static str {
+        "osc52"
+    }
+
+    fn get(&mut self) -> Result<String, ClipboardError> {
+        Err(self.error("reading the clipboard is not supported"))
+    }
+
+    fn set(&mut self, content: &str) -> Result<(), ClipboardError> {
+        let sequence = osc52_sequence(content, env::var_os("TMUX").is_some());
+        let written = match OpenOptions::new().write(true).open("/dev/tty") {
+            Ok(mut tty) => tty.write_all(sequence.as_bytes()),
+            Err(_) => io::stderr().write_all(sequence.as_bytes()),
+        };
+        written.map_err(|err| self.error(err))
+    }
+}
+
+/// Keeps the clipboard content in memory, so the clipboard paths can be tested headlessly.
+#[cfg(test)]
+#[derive(Debug, Default)]
+pub struct MemoryClipboard {
+    pub content: Option<String>,
+}
+
+#[cfg(test)]
+impl Clipboard for MemoryClipboard {
+    fn name(&self) -> &'static str {
+        "memory"
+    }
+
+    fn get(&mut self) -> Result<String, ClipboardError> {
+        self.content
+            .clone()
+            .ok_or_else(|| self.error("clipboard is empty"))
+    }
+
+    fn set(&mut self, content: &str) -> Result<(), ClipboardError> {
+        self.content = Some(content.to_owned());
+        Ok(())
+    }
+}
+
+fn osc52_sequence(content: &str, tmux: bool) -> String {
+    let sequence = format!("\x1b]52;c;{}\x07", base64(content.as_bytes()));
+    if tmux {
+        format!("\x1bPtmux;\x1b{sequence}\x1b\\")
+    } else {
+        sequence
+    }
+}
+
+fn base64(bytes: &[u8]) -> String {
+    const ALPHABET: &[u8; 64] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+    let mut encoded = String::with_capacity(bytes.len().div_ceil(3) * 4);
+    for chunk in bytes.chunks(3) {
+        let buf = [
+            chunk[0],
+            *chunk.get(1).unwrap_or(&0),
+            *chunk.get(2).unwrap_or(&0),
+        ];
+        let group = (buf[0] as u32) << 16 | (buf[1] as u32) << 8 | buf[2] as u32;
+        for i in 0..4 {
+            if i <= chunk.len() {
+                encoded.push(ALPHABET[(group >> (18 - i * 6)) as usize & 0x3f] as char);
+            } else {
+                encoded.push('=');
+            }
+        }
+    }
+    encoded
+}
+
+fn in_path(program: &str) -> bool {
+    env::var_os("PATH")
+        .map(|paths| env::split_paths(&paths).any(|dir| Path::new(&dir).join(program).is_file()))
+        .unwrap_or(false)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_base64() {
+        assert_eq!("", base64(b""));
+        assert_eq!("Zg==", base64(b"f"));
+        assert_eq!("Zm8=", base64(b"fo"));
+        assert_eq!("Zm9v", base64(b"foo"));
+        assert_eq!("5Lit5paH", base64("中文".as_bytes()));
+    }
+
+    #[test]
+    fn test_osc52_sequence() {
+        assert_eq!("\x1b]52;c;Zm9v\x07", osc52_sequence("foo", false));
+        assert_eq!(
+            "\x1bPtmux;\x1b\x1b]52;c;Zm9v\x07\x1b\\",
+            osc52_sequence("foo", true)
+        );
+    }
+
+    #[test]
+    fn test_memory_clipboard() {
+        let mut clipboard = MemoryClipboard::default();
+        let err = clipboard.get().unwrap_err();
+        assert_eq!("memory", err.backend);
+        clipboard.set("# A").unwrap();
+        assert_eq!(Ok("# A".to_owned()), clipboard.get());
+    }
+}
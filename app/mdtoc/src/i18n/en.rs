@@ -0,0 +1,119 @@
+use super::{join, located, Message};
+use crate::stats::Age;
+use crate::{CheckError, ErrKind, IoError, ParamError};
+
+pub use mdtoc_core::format::TOC_TITLE;
+
+pub const STATS_COLUMNS: [&str; 7] = ["Section", "Words", "Read", "Code", "Images", "Links", "Age"];
+
+pub fn message(message: &Message) -> String {
+    match message {
+        Message::SkippedLevel { previous, level } => {
+            format!(
+                "H{previous} is followed by H{level}, expect H{}",
+                previous + 1
+            )
+        }
+        Message::MultipleH1 { first } => format!("another H1, the first H1 is on line {first}"),
+        Message::DuplicateTitle { title, line } => format!(
+            "`{title}` duplicates the sibling heading on line {line}, its anchor is ambiguous"
+        ),
+        Message::EmptyHeading => "heading has no text".to_owned(),
+        Message::TrailingPunctuation(c) => format!("heading ends with `{c}`"),
+        Message::InconsistentNumbering(expected) => format!("expect `{expected}`"),
+        Message::MissingFile(target) => format!("`{target}` links to a file that does not exist"),
+        Message::MissingAnchor { target, closest } => match closest {
+            Some(anchor) => {
+                format!("`{target}` links to a missing anchor, did you mean `#{anchor}`?")
+            }
+            None => format!("`{target}` links to a missing anchor"),
+        },
+        Message::ChangedAnchor { old, new } => format!("`#{old}` is now `#{new}`"),
+        Message::RemovedAnchor(old) => format!("`#{old}` was removed"),
+        Message::Changed => "changed".to_owned(),
+        Message::Unchanged => "unchanged".to_owned(),
+        Message::Failed => "failed".to_owned(),
+        Message::Summary {
+            changed,
+            unchanged,
+            failed,
+        } => format!("{changed} changed, {unchanged} unchanged, {failed} failed"),
+        Message::ReadingTime(minutes) => format!("{minutes} min"),
+        Message::Age(age) => match age {
+            Age::Today => "today".to_owned(),
+            Age::Days(count) => plural(*count, "day"),
+            Age::Weeks(count) => plural(*count, "week"),
+            Age::Months(count) => plural(*count, "month"),
+            Age::Years(count) => plural(*count, "year"),
+        },
+    }
+}
+
+fn plural(count: u64, unit: &str) -> String {
+    match count {
+        1 => format!("1 {unit}"),
+        count => format!("{count} {unit}s"),
+    }
+}
+
+pub fn error(err: &ErrKind) -> String {
+    match err {
+        ErrKind::InvalidParam(err) => format!("Invalid Param: {}", param(err)),
+        ErrKind::IoErr(err) => format!("IO Error: {}", io(err)),
+        ErrKind::ClipboardErr(err) => format!("Clipboard Error: {err}"),
+        ErrKind::CheckFailed(err) => format!("Check Failed: {}", check(err)),
+        ErrKind::LintFailed { problems } => format!("Lint Failed: {problems} problem(s) found"),
+    }
+}
+
+fn param(err: &ParamError) -> String {
+    match err {
+        ParamError::Clap(msg) | ParamError::Config(msg) => msg.clone(),
+        ParamError::Setting { source, err } => format!("{source}: {err}"),
+        ParamError::MinAboveMax => "`--min-level` is greater than `--max-level`".to_owned(),
+        ParamError::Conflict { option, others } => {
+            format!("{option} conflicts with {}", join(others, ", ", " and "))
+        }
+        ParamError::Require { option, options } => {
+            format!("{option} requires {}", join(options, ", ", " or "))
+        }
+        ParamError::PathsConflict(others) => {
+            format!("paths conflict with {}", join(others, ", ", " and "))
+        }
+        ParamError::PathsRequire(options) => {
+            format!("paths require {}", join(options, ", ", " or "))
+        }
+        ParamError::ChangedRequiresGit => {
+            "`--changed` requires files in a git repository".to_owned()
+        }
+        ParamError::SingleInput(command) => format!("`{command}` takes a single input file"),
+        ParamError::NoInput => "`-f, --file`, `--from-clip` or `-` is required".to_owned(),
+        ParamError::NoSection(path) => format!("no section `{path}`"),
+        ParamError::NoSplitLevel(level) => format!("no H{level} heading to split at"),
+        ParamError::Shift(err) => format!(
+            "{}:{}: the H{} heading would become H{}, outside H1 to H6",
+            err.file.display(),
+            err.line,
+            err.level,
+            err.shifted
+        ),
+        // The library words its errors in English.
+        ParamError::Document { file, err } => located(file.as_deref(), err),
+    }
+}
+
+fn io(err: &IoError) -> String {
+    match err {
+        IoError::Io { file, err } => located(file.as_deref(), err),
+        IoError::Encoding { file, err } => located(file.as_deref(), err),
+        IoError::Git { file, err } => format!("{file}: {err}"),
+        IoError::FilesFailed(count) => format!("{count} file(s) failed"),
+    }
+}
+
+fn check(err: &CheckError) -> String {
+    match err {
+        CheckError::OutOfDate(file) => format!("`{file}` is out of date"),
+        CheckError::FilesOutOfDate(count) => format!("{count} file(s) are out of date"),
+    }
+}
@@ -0,0 +1,265 @@
+//! Message catalogs. The help of the command line is written in English and translated by the
+//! catalog of the language, while errors and the messages of reports carry their data and are
+//! worded by each catalog.
+
+mod en;
+mod zh_cn;
+
+use crate::stats::Age;
+use crate::ErrKind;
+use std::fmt::{Display, Formatter};
+use std::str::FromStr;
+use std::sync::OnceLock;
+
+static LANG: OnceLock<Lang> = OnceLock::new();
+
+/// The language variables of the locale, in order of precedence.
+const LOCALE_VARS: [&str; 3] = ["LC_ALL", "LC_MESSAGES", "LANG"];
+
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+pub enum Lang {
+    #[default]
+    En,
+    ZhCn,
+}
+
+impl FromStr for Lang {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "en" => Ok(Lang::En),
+            "zh-CN" => Ok(Lang::ZhCn),
+            _ => Err(format!("unknown language `{s}`, expect one of en, zh-CN")),
+        }
+    }
+}
+
+impl Lang {
+    /// The language of a locale such as `zh_CN.UTF-8`. Chinese is shown in simplified
+    /// characters whatever the region.
+    fn from_locale(locale: &str) -> Option<Lang> {
+        match locale.split(['_', '-', '.', '@']).next() {
+            Some("en" | "C" | "POSIX") => Some(Lang::En),
+            Some("zh") => Some(Lang::ZhCn),
+            _ => None,
+        }
+    }
+}
+
+/// Chooses the language of the messages from the `--lang` argument, or else from the locale,
+/// falling back to English.
+pub fn init(args: &[String]) -> Lang {
+    let lang = from_args(args)
+        .or_else(|| from_locale(|var| std::env::var(var).ok()))
+        .unwrap_or_default();
+    *LANG.get_or_init(|| lang)
+}
+
+pub fn lang() -> Lang {
+    LANG.get().copied().unwrap_or_default()
+}
+
+/// The value of `--lang`, which is read before the arguments are parsed so that the help is
+/// localized. An invalid one is left for the parser to report.
+fn from_args(args: &[String]) -> Option<Lang> {
+    let mut args = args.iter().take_while(|arg| *arg != "--");
+    while let Some(arg) = args.next() {
+        let value = match arg.strip_prefix("--lang") {
+            Some("") => args.next()?.as_str(),
+            Some(value) => match value.strip_prefix('=') {
+                Some(value) => value,
+                None => continue,
+            },
+            None => continue,
+        };
+        return value.parse().ok();
+    }
+    None
+}
+
+/// The language of the first locale variable that is set and not empty, as in POSIX.
+fn from_locale(var: impl Fn(&str) -> Option<String>) -> Option<Lang> {
+    let locale = LOCALE_VARS
+        .iter()
+        .filter_map(|name| var(name))
+        .find(|locale| !locale.is_empty())?;
+    Lang::from_locale(&locale)
+}
+
+/// A message of a report, a batch or a table.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Message {
+    SkippedLevel {
+        previous: u8,
+        level: u8,
+    },
+    MultipleH1 {
+        first: usize,
+    },
+    DuplicateTitle {
+        title: String,
+        line: usize,
+    },
+    EmptyHeading,
+    TrailingPunctuation(char),
+    /// A numbered title that differs from the expected one.
+    InconsistentNumbering(String),
+    MissingFile(String),
+    MissingAnchor {
+        target: String,
+        closest: Option<String>,
+    },
+    ChangedAnchor {
+        old: String,
+        new: String,
+    },
+    RemovedAnchor(String),
+    /// The outcomes of a file in a batch.
+    Changed,
+    Unchanged,
+    Failed,
+    Summary {
+        changed: usize,
+        unchanged: usize,
+        failed: usize,
+    },
+    ReadingTime(usize),
+    Age(Age),
+}
+
+impl Display for Message {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.write_str(&message(lang(), self))
+    }
+}
+
+pub fn error(lang: Lang, err: &ErrKind) -> String {
+    match lang {
+        Lang::En => en::error(err),
+        Lang::ZhCn => zh_cn::error(err),
+    }
+}
+
+pub fn message(lang: Lang, message: &Message) -> String {
+    match lang {
+        Lang::En => en::message(message),
+        Lang::ZhCn => zh_cn::message(message),
+    }
+}
+
+/// The headers of the columns of the `stats` table.
+pub fn stats_columns(lang: Lang) -> [&'static str; 7] {
+    match lang {
+        Lang::En => en::STATS_COLUMNS,
+        Lang::ZhCn => zh_cn::STATS_COLUMNS,
+    }
+}
+
+/// The title of a TOC, where a format requires one and none is set.
+pub fn toc_title(lang: Lang) -> &'static str {
+    match lang {
+        Lang::En => en::TOC_TITLE,
+        Lang::ZhCn => zh_cn::TOC_TITLE,
+    }
+}
+
+/// The translation of a text of the help, which is `None` in English.
+pub fn help(lang: Lang, key: &str) -> Option<&'static str> {
+    let catalog = match lang {
+        Lang::En => return None,
+        Lang::ZhCn => zh_cn::HELP,
+    };
+    catalog
+        .iter()
+        .find(|(name, _)| *name == key)
+        .map(|(_, text)| *text)
+}
+
+/// Joins the items of a list, with `last` before the last one, such as `a, b and c`.
+fn join(items: &[&str], separator: &str, last: &str) -> String {
+    match items {
+        [] => String::new(),
+        [item] => (*item).to_owned(),
+        [init @ .., item] => format!("{}{last}{item}", init.join(separator)),
+    }
+}
+
+/// Prefixes a message with the file it is about.
+fn located(file: Option<&str>, msg: impl std::fmt::Display) -> String {
+    match file {
+        Some(file) => format!("{file}: {msg}"),
+        None => msg.to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{CheckError, ParamError};
+
+    #[test]
+    fn test_lang() {
+        let args = |args: &str| args.split(' ').map(str::to_owned).collect::<Vec<_>>();
+        assert_eq!(Some(Lang::ZhCn), from_args(&args("mdtoc toc --lang zh-CN")));
+        assert_eq!(Some(Lang::En), from_args(&args("mdtoc --lang=en toc")));
+        assert_eq!(None, from_args(&args("mdtoc toc -- --lang zh-CN")));
+        assert_eq!(None, from_args(&args("mdtoc toc --lang fr")));
+
+        let locale = |vars: &[(&str, &str)]| {
+            let vars: Vec<(String, String)> = vars
+                .iter()
+                .map(|(name, value)| (name.to_string(), value.to_string()))
+                .collect();
+            from_locale(move |var| {
+                vars.iter()
+                    .find(|(name, _)| name == var)
+                    .map(|(_, value)| value.clone())
+            })
+        };
+        assert_eq!(Some(Lang::ZhCn), locale(&[("LANG", "zh_CN.UTF-8")]));
+        assert_eq!(
+            Some(Lang::En),
+            locale(&[
+                ("LC_ALL", ""),
+                ("LC_MESSAGES", "C"),
+                ("LANG", "zh_CN.UTF-8")
+            ])
+        );
+        assert_eq!(None, locale(&[("LANG", "fr_FR.UTF-8")]));
+        assert_eq!(None, locale(&[]));
+    }
+
+    #[test]
+    fn test_error() {
+        let conflict = ErrKind::InvalidParam(ParamError::Conflict {
+            option: "`index`",
+            others: &["`-f, --file`", "`--from-clip`", "`-`"],
+        });
+        assert_eq!(
+            "Invalid Param: `index` conflicts with `-f, --file`, `--from-clip` and `-`",
+            error(Lang::En, &conflict)
+        );
+        assert_eq!(
+            "参数无效：`index` 不能与 `-f, --file`、`--from-clip` 和 `-` 同时使用",
+            error(Lang::ZhCn, &conflict)
+        );
+        let stale = ErrKind::CheckFailed(CheckError::FilesOutOfDate(2));
+        assert_eq!("检查失败：2 个文件已过期", error(Lang::ZhCn, &stale));
+        assert_eq!("目录", toc_title(Lang::ZhCn));
+        let anchor = Message::MissingAnchor {
+            target: "#instal".to_owned(),
+            closest: Some("install".to_owned()),
+        };
+        assert_eq!(
+            "`#instal` links to a missing anchor, did you mean `#install`?",
+            message(Lang::En, &anchor)
+        );
+        assert_eq!(
+            "`#instal` 链接到不存在的锚点，是否是指 `#install`？",
+            message(Lang::ZhCn, &anchor)
+        );
+        assert_eq!("3 个月", message(Lang::ZhCn, &Message::Age(Age::Months(3))));
+        assert_eq!(None, help(Lang::En, "command.toc"));
+    }
+}
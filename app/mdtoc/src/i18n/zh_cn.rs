@@ -0,0 +1,274 @@
+use super::{join, located, Message};
+use crate::stats::Age;
+use crate::{CheckError, ErrKind, IoError, ParamError};
+use mdtoc_core::Error;
+
+pub const TOC_TITLE: &str = "目录";
+
+pub const STATS_COLUMNS: [&str; 7] = ["章节", "字数", "阅读", "代码", "图片", "链接", "年龄"];
+
+/// The help, by `command.NAME` for a command, `arg.NAME`, or `arg.COMMAND.NAME` where it differs
+/// between commands, for an argument, and `heading.NAME` for a heading of clap. Paragraphs after
+/// the first are only shown by `--help`.
+pub const HELP: &[(&str, &str)] = &[
+    ("heading.usage", "用法"),
+    ("heading.commands", "命令"),
+    ("heading.arguments", "参数"),
+    ("heading.options", "选项"),
+    ("command.mdtoc", "Markdown 目录工具。"),
+    ("command.toc", "生成 Markdown 文档的目录。"),
+    ("command.number", "为标题插入序号。"),
+    (
+        "command.insert",
+        "将目录插入 Markdown。\n\n目录会替换 `<!-- toc -->` 与 `<!-- tocstop -->` 之间的目录，\
+         没有标记时插入到第一个 H1 或 front matter 之后。",
+    ),
+    (
+        "command.ids",
+        "为标题写入显式 ID。\n\n每个列入目录且没有显式 ID 的标题会在前一行以 `<a id=\"...\">` 标签写入当前锚点，\
+         使用 `--id-style attribute` 时则写入行尾的 `{#id}`，这样修改标题后链接依然有效。\
+         已有的显式 ID，包括 HTML 标题的 `id`，会被保留，目录优先链接到它们。",
+    ),
+    (
+        "command.check",
+        "打印 `-o`、`-i`、`-t` 或 `--ids` 将会做出的修改。\n\n不写入任何内容，结果过期时退出码为 4。",
+    ),
+    (
+        "command.lint",
+        "报告标题结构问题。\n\n问题包括跳级、多个 H1、锚点有歧义的同级重复标题、空标题、末尾标点，\
+         以及使用 `-o` 时与标题序号不符的编号。问题以 `file:line: [rule] message` 的形式报告，\
+         发现任何问题时退出码为 5。",
+    ),
+    (
+        "command.links",
+        "报告失效的锚点链接。\n\n报告文件或锚点不存在的 `#fragment` 链接和相对的 `other.md#fragment` 链接，\
+         并给出最接近的锚点。锚点与目录一样按 `--slug-style` 计算，不会访问网络。\
+         发现任何问题时退出码为 5。",
+    ),
+    (
+        "command.stats",
+        "报告每个章节的统计信息。\n\n章节包括其子章节，统计字数（每个中日韩字符计为一个字）、阅读时间、\
+         代码块、图片和链接。章节的年龄是 `git blame` 中其最新一行的年龄，不在 git 仓库中时为 `-`。",
+    ),
+    (
+        "command.extract",
+        "打印标题路径下的章节。\n\n路径由 `>` 分隔的嵌套标题组成，例如 `\"Install > Linux\"`，\
+         标题按纯文本比较并忽略大小写。章节在下一个同级或更高级的标题之前结束。",
+    ),
+    (
+        "command.split",
+        "按某一级标题将文档拆分为多个文件。\n\n各章以 `01-title.md` 的形式写入 `--out-dir`，\
+         其标题平移到从 H1 开始。留下的索引中每个章节被替换为指向该章的链接，并写到输出。\
+         片段链接会改写为指向包含其锚点的文件。",
+    ),
+    (
+        "command.merge",
+        "将多个文档合并为一个。\n\nfront matter 会被去掉，标题下移 `--shift` 级。\
+         片段链接以及被合并文件之间的相对链接会改写为合并后文档的锚点。",
+    ),
+    (
+        "command.index",
+        "生成目录中 Markdown 文件的索引。\n\n文件以其第一个标题为名，`SUMMARY.md` 和 `_sidebar.md` 不计入。",
+    ),
+    ("command.completions", "打印 shell 补全脚本。"),
+    ("command.man", "打印 man 手册页。"),
+    ("command.help", "打印此信息或指定子命令的帮助"),
+    (
+        "after-help",
+        "文件为 `-` 或标准输入是管道时从标准输入读取，未指定输出时写到标准输出。\n\
+         单个文件参数是输入文件，多个文件、目录或 glob 模式则配合 `--in-place` 或 `--check`\n\
+         批量处理，并跳过 `.git` 和被 `.gitignore` 忽略的文件。\n\
+         \n\
+         默认设置读取自输入文件所在目录或其上级目录中最近的 `.mdtoc.toml`，或 `.config.toml`\n\
+         的 `[mdtoc]` 表。其中的键即长选项 `toc`、`title-order`、`order-style`、`insert`、`ids`、\n\
+         `id-style`、`slug-style`、`min-level`、`max-level`、`format`、`collapsible`、`stats`、\n\
+         `toc-title`、`to-clip`、`clipboard`、`encoding`、`in-place`、`backup`、`check`、\n\
+         `index-format`、`index-sections` 和 `lint-format`，每个 `[[overrides]]` 表为匹配其 `glob`\n\
+         （相对于配置文件）的文件设置这些键。命令行给出的选项优先。\n\
+         \n\
+         早期版本的 `-t`、`-o` 和 `-i` 仍然选择 `toc`、`number` 和 `insert`，`-fc` 和 `-tc`\n\
+         表示 `--from-clip` 和 `--to-clip`。\n\
+         \n\
+         消息的语言取自 `--lang`，或者 `LC_ALL`、`LC_MESSAGES` 或 `LANG` 环境变量。",
+    ),
+    ("arg.help", "打印帮助"),
+    ("arg.help.subcommand", "打印子命令的帮助"),
+    ("arg.version", "打印版本"),
+    ("arg.lang", "消息的语言，默认取自区域设置。"),
+    ("arg.file", "从文件读取 Markdown 内容。"),
+    ("arg.from_clip", "从剪贴板读取 Markdown 内容。"),
+    (
+        "arg.encoding",
+        "输入和输出的编码，例如 `gbk`。UTF-8 和 UTF-16 会被自动检测。",
+    ),
+    (
+        "arg.paths",
+        "输入文件，`-` 表示标准输入；或者批量处理的文件、目录和 glob 模式。",
+    ),
+    ("arg.order_style", "标题序号样式。"),
+    ("arg.slug_style", "锚点样式。"),
+    ("arg.min_level", "目录和标题序号的最低标题级别。"),
+    (
+        "arg.max_level",
+        "目录和标题序号的最高标题级别。\n\n标记了 `<!-- omit in toc -->` 的标题，以及\
+         `<!-- toc:ignore-start -->` 与 `<!-- toc:ignore-end -->` 之间的章节总是被排除。\
+         front matter 的 `toc` 键可以为每个文件设置 `depth`、`min-level`、`max-level` 和 `title`。",
+    ),
+    ("arg.write", "将结果写入文件，`-` 表示标准输出。"),
+    ("arg.to_clip", "将结果写入剪贴板。"),
+    ("arg.clipboard", "剪贴板后端，默认根据会话检测。"),
+    ("arg.in_place", "以原子方式将结果写回输入文件。"),
+    ("arg.backup", "使用 `--in-place` 时保留输入文件的 `.bak` 副本。"),
+    ("arg.check", "打印将要修改的差异，而不写入。"),
+    (
+        "arg.watch",
+        "持续运行，并在文件改变时重新处理。\n\n轮询文件的改变，文件停止改变半秒后才会处理。\
+         监视的目录中新增的文件也会被处理。",
+    ),
+    ("arg.markup", "输入的标记语言，默认根据文件扩展名检测。"),
+    ("arg.toc", "生成目录。"),
+    ("arg.title_order", "为标题插入序号。"),
+    ("arg.insert", "将目录插入 Markdown。"),
+    ("arg.ids", "为标题写入显式 ID。"),
+    ("arg.id_style", "显式 ID 的语法。"),
+    (
+        "arg.changed",
+        "列出自文件的上一个 git 版本，或 `REV`，以来改变或删除的锚点。\n\n文件有未提交的修改时，\
+         上一个版本是 `HEAD`，否则是最后一次修改它的提交之前的版本。不写入任何内容。",
+    ),
+    ("arg.format", "目录格式。"),
+    ("arg.collapsible", "将 HTML 目录包在可折叠的 `<details>` 中。"),
+    ("arg.stats", "在每个目录项后附加其章节的字数和阅读时间。"),
+    (
+        "arg.toc_title",
+        "目录的标题，显示在目录上方，或可折叠的 HTML 目录的摘要中。",
+    ),
+    ("arg.print_config", "打印生效的设置及每项设置的来源。"),
+    ("arg.lint.title_order", "检查编号是否与标题顺序一致。"),
+    ("arg.links.title_order", "检查编号是否与标题顺序一致。"),
+    ("arg.lint_format", "报告格式。"),
+    ("arg.stats_format", "报告格式：每个文件一张表格，或 JSON。"),
+    ("arg.dir", "要生成索引的目录。"),
+    (
+        "arg.index_format",
+        "索引格式：Markdown 列表、mdBook 的 `SUMMARY.md` 或 docsify 的 `_sidebar.md`。",
+    ),
+    ("arg.index_sections", "在索引中包含每个文件的 H2 章节。"),
+    ("arg.heading", "标题路径，例如 `\"Install > Linux\"`。"),
+    ("arg.level", "开始新一章的标题级别。"),
+    ("arg.out_dir", "各章文件的目录，默认为输入文件所在的目录。"),
+    ("arg.split.in_place", "用索引替换输入文件。"),
+    ("arg.files", "要合并的文件，按合并顺序排列。"),
+    ("arg.title", "将合并后的文档放在以此为标题的 H1 之下。"),
+    ("arg.shift", "标题下移的级数。"),
+];
+
+pub fn message(message: &Message) -> String {
+    match message {
+        Message::SkippedLevel { previous, level } => {
+            format!("H{previous} 之后是 H{level}，应为 H{}", previous + 1)
+        }
+        Message::MultipleH1 { first } => format!("又一个 H1，第一个 H1 在第 {first} 行"),
+        Message::DuplicateTitle { title, line } => {
+            format!("`{title}` 与第 {line} 行的同级标题重复，其锚点有歧义")
+        }
+        Message::EmptyHeading => "标题没有文本".to_owned(),
+        Message::TrailingPunctuation(c) => format!("标题以 `{c}` 结尾"),
+        Message::InconsistentNumbering(expected) => format!("应为 `{expected}`"),
+        Message::MissingFile(target) => format!("`{target}` 链接到不存在的文件"),
+        Message::MissingAnchor { target, closest } => match closest {
+            Some(anchor) => format!("`{target}` 链接到不存在的锚点，是否是指 `#{anchor}`？"),
+            None => format!("`{target}` 链接到不存在的锚点"),
+        },
+        Message::ChangedAnchor { old, new } => format!("`#{old}` 现在是 `#{new}`"),
+        Message::RemovedAnchor(old) => format!("`#{old}` 已被删除"),
+        Message::Changed => "已修改".to_owned(),
+        Message::Unchanged => "未修改".to_owned(),
+        Message::Failed => "失败".to_owned(),
+        Message::Summary {
+            changed,
+            unchanged,
+            failed,
+        } => format!("{changed} 个已修改，{unchanged} 个未修改，{failed} 个失败"),
+        Message::ReadingTime(minutes) => format!("{minutes} 分钟"),
+        Message::Age(age) => match age {
+            Age::Today => "今天".to_owned(),
+            Age::Days(count) => format!("{count} 天"),
+            Age::Weeks(count) => format!("{count} 周"),
+            Age::Months(count) => format!("{count} 个月"),
+            Age::Years(count) => format!("{count} 年"),
+        },
+    }
+}
+
+pub fn error(err: &ErrKind) -> String {
+    match err {
+        ErrKind::InvalidParam(err) => format!("参数无效：{}", param(err)),
+        ErrKind::IoErr(err) => format!("IO 错误：{}", io(err)),
+        ErrKind::ClipboardErr(err) => format!("剪贴板错误：{err}"),
+        ErrKind::CheckFailed(err) => format!("检查失败：{}", check(err)),
+        ErrKind::LintFailed { problems } => format!("检查出问题：发现 {problems} 个问题"),
+    }
+}
+
+fn param(err: &ParamError) -> String {
+    match err {
+        ParamError::Clap(msg) | ParamError::Config(msg) => msg.clone(),
+        ParamError::Setting { source, err } => format!("{source}: {}", core(err)),
+        ParamError::MinAboveMax => "`--min-level` 大于 `--max-level`".to_owned(),
+        ParamError::Conflict { option, others } => {
+            format!("{option} 不能与 {} 同时使用", join(others, "、", " 和 "))
+        }
+        ParamError::Require { option, options } => {
+            format!("{option} 需要 {}", join(options, "、", " 或 "))
+        }
+        ParamError::PathsConflict(others) => {
+            format!("路径不能与 {} 同时使用", join(others, "、", " 和 "))
+        }
+        ParamError::PathsRequire(options) => {
+            format!("路径需要 {}", join(options, "、", " 或 "))
+        }
+        ParamError::ChangedRequiresGit => "`--changed` 需要 git 仓库中的文件".to_owned(),
+        ParamError::SingleInput(command) => format!("`{command}` 只接受一个输入文件"),
+        ParamError::NoInput => "需要 `-f, --file`、`--from-clip` 或 `-`".to_owned(),
+        ParamError::NoSection(path) => format!("没有章节 `{path}`"),
+        ParamError::NoSplitLevel(level) => format!("没有可供拆分的 H{level} 标题"),
+        ParamError::Shift(err) => format!(
+            "{}:{}: H{} 标题将变为 H{}，超出 H1 到 H6 的范围",
+            err.file.display(),
+            err.line,
+            err.level,
+            err.shifted
+        ),
+        ParamError::Document { file, err } => located(file.as_deref(), core(err)),
+    }
+}
+
+fn io(err: &IoError) -> String {
+    match err {
+        IoError::Io { file, err } => located(file.as_deref(), err),
+        IoError::Encoding { file, err } => located(file.as_deref(), core(err)),
+        IoError::Git { file, err } => format!("{file}: {err}"),
+        IoError::FilesFailed(count) => format!("{count} 个文件处理失败"),
+    }
+}
+
+fn check(err: &CheckError) -> String {
+    match err {
+        CheckError::OutOfDate(file) => format!("`{file}` 已过期"),
+        CheckError::FilesOutOfDate(count) => format!("{count} 个文件已过期"),
+    }
+}
+
+fn core(err: &Error) -> String {
+    match err {
+        Error::UnknownValue {
+            kind,
+            value,
+            expected,
+        } => format!("未知的 {kind} `{value}`，应为 {expected} 之一"),
+        Error::InvalidNotebook(msg) => format!("无效的 notebook：{msg}"),
+        Error::Malformed(encoding) => format!("输入不是有效的 {encoding}"),
+        Error::Unmappable(encoding) => format!("结果中有 {encoding} 无法表示的字符"),
+    }
+}
@@ -0,0 +1,43 @@
+use std::fs;
+use std::io::{self, Write};
+use std::path::Path;
+use tempfile::NamedTempFile;
+
+/// Replaces the file atomically by writing a sibling temp file and renaming it over the original.
+/// Returns the metadata of the written file, taken before the rename so that a later change of
+/// the file cannot show in it.
+pub fn write_in_place(file: &str, content: &[u8], backup: bool) -> io::Result<fs::Metadata> {
+    let path = fs::canonicalize(file)?;
+    let dir = path.parent().unwrap_or(Path::new("."));
+    let permissions = fs::metadata(&path)?.permissions();
+    let mut temp = NamedTempFile::new_in(dir)?;
+    temp.write_all(content)?;
+    temp.as_file().sync_all()?;
+    fs::set_permissions(temp.path(), permissions)?;
+    if backup {
+        let mut backup_path = path.clone().into_os_string();
+        backup_path.push(".bak");
+        fs::copy(&path, backup_path)?;
+    }
+    let metadata = temp.as_file().metadata()?;
+    temp.persist(&path).map_err(|err| err.error)?;
+    Ok(metadata)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_write_in_place() {
+        let dir = tempfile::tempdir().unwrap();
+        let file = dir.path().join("README.md");
+        fs::write(&file, "# old").unwrap();
+        let file = file.to_str().unwrap();
+        let metadata = write_in_place(file, b"# new", true).unwrap();
+        assert_eq!(5, metadata.len());
+        assert_eq!("# new", fs::read_to_string(file).unwrap());
+        assert_eq!("# old", fs::read_to_string(format!("{file}.bak")).unwrap());
+        assert_eq!(2, fs::read_dir(dir.path()).unwrap().count());
+    }
+}
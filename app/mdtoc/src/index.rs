@@ -0,0 +1,277 @@
+use mdtoc_core::encoding::read_file;
+use mdtoc_core::heading::parse_headings;
+use mdtoc_core::slug::{plain_text, SlugStyle, Slugger};
+use mdtoc_core::text::Text;
+use mdtoc_core::Error;
+use std::collections::BTreeMap;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+
+const INDEX_PAGES: [&str; 3] = ["README.md", "readme.md", "index.md"];
+const GENERATED_FILES: [&str; 2] = ["SUMMARY.md", "_sidebar.md"];
+
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+pub enum IndexFormat {
+    /// A nested markdown list.
+    #[default]
+    Markdown,
+    /// `SUMMARY.md` of mdBook, section anchors are not supported by mdBook and are left out.
+    MdBook,
+    /// `_sidebar.md` of docsify.
+    Docsify,
+}
+
+impl FromStr for IndexFormat {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "markdown" => Ok(IndexFormat::Markdown),
+            "mdbook" => Ok(IndexFormat::MdBook),
+            "docsify" => Ok(IndexFormat::Docsify),
+            _ => Err(Error::UnknownValue {
+                kind: "index format",
+                value: s.to_owned(),
+                expected: "markdown, mdbook, docsify",
+            }),
+        }
+    }
+}
+
+#[derive(Debug)]
+struct Page {
+    title: String,
+    link: String,
+    sections: Vec<(String, String)>,
+}
+
+#[derive(Debug, Default)]
+struct Node {
+    page: Option<Page>,
+    files: BTreeMap<String, Page>,
+    dirs: BTreeMap<String, Node>,
+}
+
+pub struct IndexOptions {
+    pub format: IndexFormat,
+    pub sections: bool,
+    pub slug_style: SlugStyle,
+}
+
+/// Builds an index of `files` following the folder structure below `root`, with links relative
+/// to `root`.
+pub fn generate_index(
+    root: &Path,
+    files: &[PathBuf],
+    options: &IndexOptions,
+) -> io::Result<Vec<String>> {
+    let mut tree = Node::default();
+    for file in files {
+        let relative = file.strip_prefix(root).unwrap_or(file);
+        let name = relative.file_name().and_then(|name| name.to_str());
+        if name.is_some_and(|name| GENERATED_FILES.contains(&name)) {
+            continue;
+        }
+        let page = read_page(file, relative, options)?;
+        let mut node = &mut tree;
+        if let Some(parent) = relative.parent() {
+            for dir in parent.iter() {
+                node = node
+                    .dirs
+                    .entry(dir.to_string_lossy().into_owned())
+                    .or_default();
+            }
+        }
+        match name {
+            Some(name) if INDEX_PAGES.contains(&name) && node.page.is_none() => {
+                node.page = Some(page)
+            }
+            _ => {
+                node.files
+                    .insert(relative.to_string_lossy().into_owned(), page);
+            }
+        }
+    }
+
+    let mut lines = Vec::new();
+    let bullet = match options.format {
+        IndexFormat::Docsify => '*',
+        _ => '-',
+    };
+    match (options.format, &tree.page) {
+        (IndexFormat::MdBook, Some(page)) => {
+            lines.push("# Summary".to_owned());
+            lines.push(String::new());
+            lines.push(format!("[{}]({})", page.title, page.link));
+            lines.push(String::new());
+        }
+        (IndexFormat::MdBook, None) => {
+            lines.push("# Summary".to_owned());
+            lines.push(String::new());
+        }
+        (_, Some(page)) => push_page(&mut lines, page, 0, bullet, options),
+        (_, None) => {}
+    }
+    push_children(&mut lines, &tree, 0, bullet, options);
+    Ok(lines)
+}
+
+fn push_children(
+    lines: &mut Vec<String>,
+    node: &Node,
+    depth: usize,
+    bullet: char,
+    options: &IndexOptions,
+) {
+    for page in node.files.values() {
+        push_page(lines, page, depth, bullet, options);
+    }
+    for (name, dir) in &node.dirs {
+        match &dir.page {
+            Some(page) => push_page(lines, page, depth, bullet, options),
+            None if options.format == IndexFormat::MdBook => {
+                lines.push(format!("{}{bullet} [{name}]()", indent(depth)))
+            }
+            None => lines.push(format!("{}{bullet} {name}", indent(depth))),
+        }
+        push_children(lines, dir, depth + 1, bullet, options);
+    }
+}
+
+fn push_page(
+    lines: &mut Vec<String>,
+    page: &Page,
+    depth: usize,
+    bullet: char,
+    options: &IndexOptions,
+) {
+    lines.push(format!(
+        "{}{bullet} [{}]({})",
+        indent(depth),
+        page.title,
+        page.link
+    ));
+    if options.sections && options.format != IndexFormat::MdBook {
+        for (title, slug) in &page.sections {
+            lines.push(format!(
+                "{}{bullet} [{title}]({}#{slug})",
+                indent(depth + 1),
+                page.link
+            ));
+        }
+    }
+}
+
+fn read_page(file: &Path, relative: &Path, options: &IndexOptions) -> io::Result<Page> {
+    let (content, _) = read_file(file, None)
+        .map_err(|err| io::Error::new(err.kind(), format!("{}: {err}", file.display())))?;
+    let lines = Text::parse(&content).lines;
+    let headings = parse_headings(&lines);
+    let title = match headings.first() {
+        Some(heading) => plain_text(&heading.text),
+        None => relative
+            .file_stem()
+            .map_or_else(String::new, |stem| stem.to_string_lossy().into_owned()),
+    };
+    let mut slugger = Slugger::new(options.slug_style);
+    let sections = headings
+        .iter()
+        .map(|heading| (heading, heading.anchor(&mut slugger)))
+        .filter(|(heading, _)| heading.level == 2 && !heading.excluded)
+        .map(|(heading, slug)| (plain_text(&heading.text), slug))
+        .collect();
+    Ok(Page {
+        title,
+        link: to_link(relative),
+        sections,
+    })
+}
+
+fn to_link(relative: &Path) -> String {
+    relative
+        .iter()
+        .map(|component| component.to_string_lossy().replace(' ', "%20"))
+        .collect::<Vec<String>>()
+        .join("/")
+}
+
+fn indent(depth: usize) -> String {
+    "  ".repeat(depth)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+
+    fn index(format: IndexFormat, sections: bool) -> Vec<String> {
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path();
+        let files = [
+            ("README.md", "# Home"),
+            ("SUMMARY.md", "# Summary"),
+            ("intro.md", "\u{feff}# Intro\n## Why\n## How"),
+            ("guide/README.md", "# Guide"),
+            ("guide/install.md", "# Install\n## Linux"),
+            ("api/my ref.md", "no heading"),
+        ];
+        for (file, content) in files {
+            fs::create_dir_all(root.join(file).parent().unwrap()).unwrap();
+            fs::write(root.join(file), content).unwrap();
+        }
+        let mut paths: Vec<PathBuf> = files.iter().map(|(file, _)| root.join(file)).collect();
+        paths.sort();
+        let options = IndexOptions {
+            format,
+            sections,
+            slug_style: SlugStyle::GitHub,
+        };
+        generate_index(root, &paths, &options).unwrap()
+    }
+
+    #[test]
+    fn test_generate_markdown_index() {
+        let expected = vec![
+            "- [Home](README.md)",
+            "- [Intro](intro.md)",
+            "  - [Why](intro.md#why)",
+            "  - [How](intro.md#how)",
+            "- api",
+            "  - [my ref](api/my%20ref.md)",
+            "- [Guide](guide/README.md)",
+            "  - [Install](guide/install.md)",
+            "    - [Linux](guide/install.md#linux)",
+        ];
+        assert_eq!(expected, index(IndexFormat::Markdown, true));
+    }
+
+    #[test]
+    fn test_generate_mdbook_summary() {
+        let expected = vec![
+            "# Summary",
+            "",
+            "[Home](README.md)",
+            "",
+            "- [Intro](intro.md)",
+            "- [api]()",
+            "  - [my ref](api/my%20ref.md)",
+            "- [Guide](guide/README.md)",
+            "  - [Install](guide/install.md)",
+        ];
+        assert_eq!(expected, index(IndexFormat::MdBook, true));
+    }
+
+    #[test]
+    fn test_generate_docsify_sidebar() {
+        let expected = vec![
+            "* [Home](README.md)",
+            "* [Intro](intro.md)",
+            "* api",
+            "  * [my ref](api/my%20ref.md)",
+            "* [Guide](guide/README.md)",
+            "  * [Install](guide/install.md)",
+        ];
+        assert_eq!(expected, index(IndexFormat::Docsify, false));
+    }
+}
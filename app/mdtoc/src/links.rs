@@ -0,0 +1,343 @@
+use crate::batch::MARKDOWN_EXTENSIONS;
+use crate::i18n::Message;
+use crate::lint::Diagnostic;
+use mdtoc_core::encoding::read_file;
+use mdtoc_core::heading::{front_matter_end, parse_headings, FenceTracker};
+use mdtoc_core::slug::{SlugStyle, Slugger};
+use mdtoc_core::text::Text;
+use regex::Regex;
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::OnceLock;
+
+/// A link target found on the 0-based `line`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Link {
+    pub line: usize,
+    pub target: String,
+}
+
+/// Returns the anchors of the document: the slugs of all headings, generated like the TOC does,
+/// and the `id` or `name` of HTML elements.
+pub fn anchors(lines: &[String], slug_style: SlugStyle) -> Vec<String> {
+    static ID: OnceLock<Regex> = OnceLock::new();
+    let id = ID.get_or_init(|| Regex::new(r#"<[A-Za-z][^>]*?\s(?:id|name)="([^"]+)""#).unwrap());
+    let mut slugger = Slugger::new(slug_style);
+    let mut anchors: Vec<String> = parse_headings(lines)
+        .iter()
+        .map(|heading| heading.anchor(&mut slugger))
+        .collect();
+    for line in code_free_lines(lines) {
+        anchors.extend(id.captures_iter(&line.1).map(|caps| caps[1].to_owned()));
+    }
+    anchors
+}
+
+/// Scans inline links, images, reference definitions and HTML `href`s outside of code.
+pub fn find_links(lines: &[String]) -> Vec<Link> {
+    let mut links = Vec::new();
+    for (line, text) in code_free_lines(lines) {
+        for regex in link_patterns() {
+            for caps in regex.captures_iter(&text) {
+                if let Some(target) = caps.get(1).or_else(|| caps.get(2)) {
+                    links.push(Link {
+                        line,
+                        target: target.as_str().to_owned(),
+                    });
+                }
+            }
+        }
+    }
+    links
+}
+
+/// Replaces the link targets found by [`find_links`] with the result of `rewrite`, called with
+/// the 0-based line and the target, keeping the targets it returns `None` for.
+pub fn rewrite_links<F>(lines: &mut [String], mut rewrite: F)
+where
+    F: FnMut(usize, &str) -> Option<String>,
+{
+    let start = front_matter_end(lines).map_or(0, |end| end + 1);
+    let mut fence = FenceTracker::default();
+    for (index, line) in lines.iter_mut().enumerate().skip(start) {
+        if fence.is_code(line) {
+            continue;
+        }
+        let spans: Vec<_> = code_span().find_iter(line).map(|m| m.range()).collect();
+        let mut targets = Vec::new();
+        for regex in link_patterns() {
+            for caps in regex.captures_iter(line) {
+                let Some(target) = caps.get(1).or_else(|| caps.get(2)) else {
+                    continue;
+                };
+                let in_code = spans
+                    .iter()
+                    .any(|span| span.start < target.end() && target.start() < span.end);
+                if !in_code {
+                    targets.push(target.range());
+                }
+            }
+        }
+        targets.sort_by_key(|range| std::cmp::Reverse(range.start));
+        for range in targets {
+            if let Some(target) = rewrite(index, &line[range.clone()]) {
+                line.replace_range(range, &target);
+            }
+        }
+    }
+}
+
+/// Checks fragment and relative file links offline. The anchors of linked files are computed
+/// with the same slug style as the TOC and cached across documents.
+pub struct LinkChecker {
+    slug_style: SlugStyle,
+    anchors: HashMap<PathBuf, Option<Vec<String>>>,
+}
+
+impl LinkChecker {
+    pub fn new(slug_style: SlugStyle) -> Self {
+        LinkChecker {
+            slug_style,
+            anchors: HashMap::new(),
+        }
+    }
+
+    /// Checks the links of the document at `file`, or of a document read from stdin or the
+    /// clipboard if `file` is `None`, in which case files are resolved from the working directory.
+    pub fn check(&mut self, file: Option<&Path>, lines: &[String]) -> Vec<Diagnostic> {
+        let own = anchors(lines, self.slug_style);
+        let dir = file
+            .and_then(Path::parent)
+            .map_or_else(PathBuf::new, Path::to_path_buf);
+        let mut diagnostics = Vec::new();
+        for link in find_links(lines) {
+            if is_external(&link.target) {
+                continue;
+            }
+            let (path, fragment) = match link.target.split_once('#') {
+                Some((path, fragment)) => (path, Some(fragment)),
+                None => (link.target.as_str(), None),
+            };
+            let path = path.split_once('?').map_or(path, |(path, _)| path);
+            let anchors = if path.is_empty() {
+                Some(&own)
+            } else {
+                let target = dir.join(percent_decode(path));
+                if !target.exists() {
+                    diagnostics.push(Diagnostic {
+                        line: link.line + 1,
+                        rule: "missing-file",
+                        message: Message::MissingFile(link.target.clone()),
+                    });
+                    continue;
+                }
+                self.file_anchors(&target)
+            };
+            let (Some(fragment), Some(anchors)) = (fragment, anchors) else {
+                continue;
+            };
+            let fragment = percent_decode(fragment);
+            if fragment.is_empty() || anchors.iter().any(|a| percent_decode(a) == fragment) {
+                continue;
+            }
+            diagnostics.push(Diagnostic {
+                line: link.line + 1,
+                rule: "broken-anchor",
+                message: Message::MissingAnchor {
+                    target: link.target.clone(),
+                    closest: closest(&fragment, anchors).map(str::to_owned),
+                },
+            });
+        }
+        diagnostics
+    }
+
+    /// Returns the anchors of a markdown file, or `None` if it is not a readable markdown file.
+    fn file_anchors(&mut self, file: &Path) -> Option<&Vec<String>> {
+        let key = fs::canonicalize(file).unwrap_or_else(|_| file.to_path_buf());
+        let slug_style = self.slug_style;
+        self.anchors
+            .entry(key)
+            .or_insert_with(|| {
+                let extension = file.extension()?.to_str()?;
+                if !MARKDOWN_EXTENSIONS.contains(&extension) {
+                    return None;
+                }
+                let (content, _) = read_file(file, None).ok()?;
+                Some(anchors(&Text::parse(&content).lines, slug_style))
+            })
+            .as_ref()
+    }
+}
+
+/// Inline links and images, reference definitions and HTML `href`s, capturing the target.
+fn link_patterns() -> [&'static Regex; 3] {
+    static INLINE: OnceLock<Regex> = OnceLock::new();
+    static REFERENCE: OnceLock<Regex> = OnceLock::new();
+    static HREF: OnceLock<Regex> = OnceLock::new();
+    let inline = INLINE.get_or_init(|| {
+        Regex::new(r#"\]\(\s*(?:<([^>]*)>|([^)\s]+))(?:\s+(?:"[^"]*"|'[^']*'))?\s*\)"#).unwrap()
+    });
+    let reference =
+        REFERENCE.get_or_init(|| Regex::new(r"^ {0,3}\[[^\]]+\]:\s*(?:<([^>]*)>|(\S+))").unwrap());
+    let href = HREF.get_or_init(|| Regex::new(r#"\shref="([^"]*)""#).unwrap());
+    [inline, reference, href]
+}
+
+fn code_span() -> &'static Regex {
+    static CODE_SPAN: OnceLock<Regex> = OnceLock::new();
+    CODE_SPAN.get_or_init(|| Regex::new(r"(`+)[^`]+?(`+)").unwrap())
+}
+
+/// Yields the 0-based line numbers and texts of lines outside of front matter and fenced code,
+/// with code spans blanked out.
+fn code_free_lines(lines: &[String]) -> impl Iterator<Item = (usize, String)> + '_ {
+    let code_span = code_span();
+    let start = front_matter_end(lines).map_or(0, |end| end + 1);
+    let mut fence = FenceTracker::default();
+    lines
+        .iter()
+        .enumerate()
+        .skip(start)
+        .filter(move |(_, line)| !fence.is_code(line))
+        .map(|(index, line)| (index, code_span.replace_all(line, "").into_owned()))
+}
+
+pub fn is_external(target: &str) -> bool {
+    let scheme = target.split_once(':').is_some_and(|(scheme, _)| {
+        scheme
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || "+-.".contains(c))
+    });
+    scheme || target.starts_with("//") || target.starts_with('/')
+}
+
+fn percent_decode(text: &str) -> String {
+    let bytes = text.as_bytes();
+    let mut decoded = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        let hex = bytes
+            .get(i + 1..i + 3)
+            .and_then(|hex| std::str::from_utf8(hex).ok())
+            .and_then(|hex| u8::from_str_radix(hex, 16).ok());
+        match (bytes[i], hex) {
+            (b'%', Some(byte)) => {
+                decoded.push(byte);
+                i += 3;
+            }
+            (byte, _) => {
+                decoded.push(byte);
+                i += 1;
+            }
+        }
+    }
+    String::from_utf8_lossy(&decoded).into_owned()
+}
+
+/// Returns the anchor closest to `fragment`, preferring anchors that contain it or are contained
+/// in it, then the smallest edit distance.
+fn closest<'a>(fragment: &str, anchors: &'a [String]) -> Option<&'a str> {
+    anchors
+        .iter()
+        .min_by_key(|anchor| {
+            let related = anchor.contains(fragment) || fragment.contains(anchor.as_str());
+            (!related, edit_distance(fragment, anchor))
+        })
+        .map(String::as_str)
+}
+
+fn edit_distance(a: &str, b: &str) -> usize {
+    let b: Vec<char> = b.chars().collect();
+    let mut row: Vec<usize> = (0..=b.len()).collect();
+    for (i, ca) in a.chars().enumerate() {
+        let mut diagonal = row[0];
+        row[0] = i + 1;
+        for (j, cb) in b.iter().enumerate() {
+            let substitution = diagonal + usize::from(ca != *cb);
+            diagonal = row[j + 1];
+            row[j + 1] = substitution.min(row[j] + 1).min(diagonal + 1);
+        }
+    }
+    row[b.len()]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::i18n::{message, Lang};
+
+    fn lines(content: &str) -> Vec<String> {
+        content.lines().map(str::to_owned).collect()
+    }
+
+    #[test]
+    fn test_find_links() {
+        let content = lines(concat!(
+            "See [a](#a) and ![img](<my img.png> \"title\").\n",
+            "`[code](#code)` <a href=\"other.md#b\">b</a>\n",
+            "```\n[fenced](#fenced)\n```\n",
+            "[ref]: ./ref.md#c"
+        ));
+        let links = find_links(&content);
+        let targets: Vec<(usize, &str)> = links
+            .iter()
+            .map(|link| (link.line, link.target.as_str()))
+            .collect();
+        let expected = vec![
+            (0, "#a"),
+            (0, "my img.png"),
+            (1, "other.md#b"),
+            (5, "./ref.md#c"),
+        ];
+        assert_eq!(expected, targets);
+    }
+
+    #[test]
+    fn test_check_links() {
+        let dir = tempfile::tempdir().unwrap();
+        fs::write(dir.path().join("other.md"), "# Other\n## Install Guide").unwrap();
+        let file = dir.path().join("doc.md");
+        let content = lines(concat!(
+            "# Doc <a id=\"top\"></a>\n## Usage\n",
+            "[ok](#usage) [top](#top) [bad](#usages)\n",
+            "[ok](other.md#install-guide) [bad](other.md#install)\n",
+            "[missing](gone.md) [web](https://example.com/#x) [mail](mailto:a@b.c)"
+        ));
+        let diagnostics = LinkChecker::new(SlugStyle::GitHub).check(Some(&file), &content);
+        let messages: Vec<(usize, &str, String)> = diagnostics
+            .iter()
+            .map(|d| (d.line, d.rule, message(Lang::En, &d.message)))
+            .collect();
+        let expected = vec![
+            (
+                3,
+                "broken-anchor",
+                "`#usages` links to a missing anchor, did you mean `#usage`?".to_owned(),
+            ),
+            (
+                4,
+                "broken-anchor",
+                "`other.md#install` links to a missing anchor, did you mean `#install-guide`?"
+                    .to_owned(),
+            ),
+            (
+                5,
+                "missing-file",
+                "`gone.md` links to a file that does not exist".to_owned(),
+            ),
+        ];
+        assert_eq!(expected, messages);
+    }
+
+    #[test]
+    fn test_edit_distance() {
+        assert_eq!(3, edit_distance("kitten", "sitting"));
+        assert_eq!(0, edit_distance("目录", "目录"));
+        assert_eq!(
+            Some("b-c"),
+            closest("bc", &["a".to_owned(), "b-c".to_owned()])
+        );
+    }
+}
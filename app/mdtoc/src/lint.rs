@@ -0,0 +1,210 @@
+use crate::i18n::Message;
+use mdtoc_core::heading::{parse_headings, Heading, LevelRange};
+use mdtoc_core::order::{self, OrderStyle};
+use mdtoc_core::Error;
+use serde_json::{json, Value};
+use std::collections::HashMap;
+use std::str::FromStr;
+
+const TRAILING_PUNCTUATION: &[char] = &['.', ',', ';', ':', '!', '。', '，', '；', '：', '！'];
+
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+pub enum LintFormat {
+    #[default]
+    Text,
+    Json,
+}
+
+impl FromStr for LintFormat {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "text" => Ok(LintFormat::Text),
+            "json" => Ok(LintFormat::Json),
+            _ => Err(Error::UnknownValue {
+                kind: "lint format",
+                value: s.to_owned(),
+                expected: "text, json",
+            }),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Diagnostic {
+    /// 1-based line number.
+    pub line: usize,
+    pub rule: &'static str,
+    pub message: Message,
+}
+
+/// Checks the heading structure, and the title order when `title_order` is given.
+pub fn lint(lines: &[String], title_order: Option<(OrderStyle, LevelRange)>) -> Vec<Diagnostic> {
+    let headings = parse_headings(lines);
+    let mut diagnostics = Vec::new();
+    let mut report = |heading: &Heading, rule, message: Message| {
+        diagnostics.push(Diagnostic {
+            line: heading.line + 1,
+            rule,
+            message,
+        })
+    };
+
+    let mut first_h1: Option<usize> = None;
+    // Titles seen under the current parent, per level.
+    let mut siblings: Vec<HashMap<String, usize>> = vec![HashMap::new(); 7];
+    let mut previous: Option<u8> = None;
+    for heading in &headings {
+        let level = heading.level;
+        if let Some(previous) = previous {
+            if level > previous + 1 {
+                report(
+                    heading,
+                    "skipped-level",
+                    Message::SkippedLevel { previous, level },
+                );
+            }
+        }
+        previous = Some(level);
+
+        if level == 1 {
+            match first_h1 {
+                Some(line) => report(heading, "multiple-h1", Message::MultipleH1 { first: line }),
+                None => first_h1 = Some(heading.line + 1),
+            }
+        }
+
+        for titles in siblings.iter_mut().skip(level as usize + 1) {
+            titles.clear();
+        }
+        let title = heading.text.trim().to_lowercase();
+        if let Some(line) = siblings[level as usize].insert(title, heading.line + 1) {
+            report(
+                heading,
+                "duplicate-title",
+                Message::DuplicateTitle {
+                    title: heading.text.clone(),
+                    line,
+                },
+            );
+        }
+
+        if heading.text.trim().is_empty() {
+            report(heading, "empty-heading", Message::EmptyHeading);
+        } else if let Some(c) = heading.text.trim_end().chars().last() {
+            if TRAILING_PUNCTUATION.contains(&c) {
+                report(
+                    heading,
+                    "trailing-punctuation",
+                    Message::TrailingPunctuation(c),
+                );
+            }
+        }
+    }
+
+    if let Some((style, levels)) = title_order {
+        let mut ordered = lines.to_vec();
+        order::apply_title_order(&mut ordered, style, levels);
+        for heading in &headings {
+            let expected = &ordered[heading.line];
+            if expected != &lines[heading.line] {
+                report(
+                    heading,
+                    "inconsistent-numbering",
+                    Message::InconsistentNumbering(expected.trim().to_owned()),
+                );
+            }
+        }
+    }
+
+    diagnostics.sort_by_key(|diagnostic| diagnostic.line);
+    diagnostics
+}
+
+pub fn render_text(file: &str, diagnostics: &[Diagnostic]) -> Vec<String> {
+    diagnostics
+        .iter()
+        .map(|diagnostic| {
+            format!(
+                "{file}:{}: [{}] {}",
+                diagnostic.line, diagnostic.rule, diagnostic.message
+            )
+        })
+        .collect()
+}
+
+pub fn to_json(file: &str, diagnostics: &[Diagnostic]) -> Vec<Value> {
+    diagnostics
+        .iter()
+        .map(|diagnostic| {
+            json!({
+                "file": file,
+                "line": diagnostic.line,
+                "rule": diagnostic.rule,
+                "message": diagnostic.message.to_string(),
+            })
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn lines(content: &str) -> Vec<String> {
+        content.lines().map(str::to_owned).collect()
+    }
+
+    fn rules(content: &str, title_order: Option<(OrderStyle, LevelRange)>) -> Vec<(usize, &str)> {
+        lint(&lines(content), title_order)
+            .iter()
+            .map(|diagnostic| (diagnostic.line, diagnostic.rule))
+            .collect()
+    }
+
+    #[test]
+    fn test_lint() {
+        let content = "# A\n## B\n#### C\n## B\n### D\n## E.\n# F\n##\n### D";
+        let expected = vec![
+            (3, "skipped-level"),
+            (4, "duplicate-title"),
+            (6, "trailing-punctuation"),
+            (7, "multiple-h1"),
+            (8, "empty-heading"),
+        ];
+        assert_eq!(expected, rules(content, None));
+    }
+
+    #[test]
+    fn test_lint_numbering() {
+        let content = "# 1 A\n## 1.1 B\n## 1.3 C\n# D";
+        let expected = vec![(3, "inconsistent-numbering"), (4, "inconsistent-numbering")];
+        let title_order = Some((OrderStyle::Decimal, LevelRange { min: 2, max: 6 }));
+        assert_eq!(vec![(4, "multiple-h1")], rules(content, None));
+        let mut found = rules(content, title_order);
+        found.retain(|(_, rule)| *rule == "inconsistent-numbering");
+        assert_eq!(
+            vec![(2, "inconsistent-numbering"), (3, "inconsistent-numbering")],
+            found
+        );
+        let title_order = Some((OrderStyle::Decimal, LevelRange::default()));
+        let mut found = rules(content, title_order);
+        found.retain(|(_, rule)| *rule == "inconsistent-numbering");
+        assert_eq!(expected, found);
+    }
+
+    #[test]
+    fn test_render() {
+        let diagnostics = lint(&lines("# A\n### B"), None);
+        assert_eq!(
+            vec!["a.md:2: [skipped-level] H1 is followed by H3, expect H2"],
+            render_text("a.md", &diagnostics)
+        );
+        assert_eq!(
+            json!([{"file": "a.md", "line": 2, "rule": "skipped-level",
+                    "message": "H1 is followed by H3, expect H2"}]),
+            Value::Array(to_json("a.md", &diagnostics))
+        );
+    }
+}
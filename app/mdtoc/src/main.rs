@@ -1,38 +1,142 @@
+mod batch;
+mod changed;
+mod check;
+mod cli;
+mod clipboard;
+mod i18n;
+mod in_place;
+mod index;
+mod links;
+mod lint;
+mod project;
+mod sections;
+mod stats;
+mod watch;
+
+use batch::{Outcome, MARKDOWN_EXTENSIONS};
+use clap::error::ErrorKind::DisplayHelpOnMissingArgumentOrSubcommand;
+use cli::Action;
+use clipboard::{Backend, Clipboard, ClipboardError};
+use index::{IndexFormat, IndexOptions};
+use links::LinkChecker;
+use lint::LintFormat;
+use mdtoc_core::encoding::{read_file, Encoding};
+use mdtoc_core::format::TocFormat;
+use mdtoc_core::heading::LevelRange;
+use mdtoc_core::ids::IdStyle;
+use mdtoc_core::markup::{self, Markup};
+use mdtoc_core::order::OrderStyle;
+use mdtoc_core::slug::SlugStyle;
+use mdtoc_core::text::Text;
+use mdtoc_core::toc::TocOptions;
+use mdtoc_core::{front_matter, Document};
+use sections::{MergeOptions, ShiftError, SplitOptions};
+use std::collections::BTreeMap;
 use std::fmt::{Debug, Display, Formatter};
-use std::fs::File;
-use std::io::{BufRead, BufReader, Cursor};
+use std::fs;
+use std::io::{IsTerminal, Read, Write};
+use std::path::{Path, PathBuf};
 use std::process::{ExitCode, Termination};
+use std::str::FromStr;
+use std::time::{SystemTime, UNIX_EPOCH};
 use std::{error, io};
+use toml::Value;
 
-const VERSION: &'static str = "1.0.0";
-const USAGE: &'static str = r#"mdtoc 1.0.0: Markdown TOC tool.
+const STDIO: &str = "-";
+const VERSION: &str = "1.0.0";
 
-Usage: mdtoc [option]... [argument]...
+/// An error, carrying its data rather than a message, which is rendered by the catalog of the
+/// language in `Display`.
+enum ErrKind {
+    InvalidParam(ParamError),
+    IoErr(IoError),
+    ClipboardErr(ClipboardError),
+    CheckFailed(CheckError),
+    LintFailed { problems: usize },
+}
 
-Options and arguments:
-        -f, --file <file>       Read markdown content from file.
-        -fc, --from-clip        Read markdown content from clipboard.
-        -t, --toc               Generate TOC.
-        -o, --title-order       Insert order number to title.
-        -i, --insert            Insert TOC into the header of the markdown.
-        -w, --write <file>      Write result to file.
-        -tc, --to-clip          Write result to clipboard.
-        -h, --help              Show this help.
-        -v, --version           Show version."#;
+enum ParamError {
+    /// A message of clap, which is only in English.
+    Clap(String),
+    /// A config file that cannot be read, or paths that cannot be collected.
+    Config(String),
+    /// A setting of a config file with an invalid value.
+    Setting {
+        source: String,
+        err: mdtoc_core::Error,
+    },
+    MinAboveMax,
+    /// An option, or a command, used together with any of `others`.
+    Conflict {
+        option: &'static str,
+        others: &'static [&'static str],
+    },
+    /// An option, or a command, used without any of `options`.
+    Require {
+        option: &'static str,
+        options: &'static [&'static str],
+    },
+    PathsConflict(&'static [&'static str]),
+    PathsRequire(&'static [&'static str]),
+    ChangedRequiresGit,
+    /// A command given several input files.
+    SingleInput(&'static str),
+    NoInput,
+    NoSection(String),
+    NoSplitLevel(u8),
+    /// A heading that `split` or `merge` would move above H1 or below H6.
+    Shift(ShiftError),
+    Document {
+        file: Option<String>,
+        err: mdtoc_core::Error,
+    },
+}
 
-enum ErrKind {
-    InvalidParam(String),
-    IoErr(String),
-    ClipboardErr(String),
+enum IoError {
+    Io {
+        file: Option<String>,
+        err: String,
+    },
+    /// Text that cannot be decoded from, or encoded in, its encoding.
+    Encoding {
+        file: Option<String>,
+        err: mdtoc_core::Error,
+    },
+    /// A failed git command, with its error output.
+    Git {
+        file: String,
+        err: String,
+    },
+    FilesFailed(usize),
+}
+
+enum CheckError {
+    OutOfDate(String),
+    FilesOutOfDate(usize),
+}
+
+impl ErrKind {
+    /// An I/O error, or an encoding error if `read_file` failed to decode the file.
+    fn io(file: Option<&str>, err: io::Error) -> ErrKind {
+        let inner = err.get_ref().and_then(|inner| inner.downcast_ref());
+        match inner {
+            Some(inner) => ErrKind::encoding(file, mdtoc_core::Error::clone(inner)),
+            None => ErrKind::IoErr(IoError::Io {
+                file: file.map(str::to_owned),
+                err: err.to_string(),
+            }),
+        }
+    }
+
+    fn encoding(file: Option<&str>, err: mdtoc_core::Error) -> ErrKind {
+        let file = file.map(str::to_owned);
+        ErrKind::IoErr(IoError::Encoding { file, err })
+    }
 }
 
 impl Display for ErrKind {
     fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
-        match self {
-            ErrKind::InvalidParam(msg) => write!(f, "Invalid Param: {}", msg),
-            ErrKind::IoErr(msg) => write!(f, "IO Error: {}", msg),
-            ErrKind::ClipboardErr(msg) => write!(f, "Clipboard Error: {}", msg),
-        }
+        f.write_str(&i18n::error(i18n::lang(), self))
     }
 }
 
@@ -50,125 +154,1035 @@ impl Termination for ErrKind {
             ErrKind::InvalidParam(_) => ExitCode::from(1),
             ErrKind::IoErr(_) => ExitCode::from(2),
             ErrKind::ClipboardErr(_) => ExitCode::from(3),
+            ErrKind::CheckFailed(_) => ExitCode::from(4),
+            ErrKind::LintFailed { .. } => ExitCode::from(5),
         }
     }
 }
 
 impl From<io::Error> for ErrKind {
     fn from(value: io::Error) -> Self {
-        ErrKind::IoErr(format!("{value}"))
+        ErrKind::io(None, value)
     }
 }
-impl From<clipboard_win::ErrorCode> for ErrKind {
-    fn from(value: clipboard_win::ErrorCode) -> Self {
-        ErrKind::ClipboardErr(format!("{value}"))
+impl From<ClipboardError> for ErrKind {
+    fn from(value: ClipboardError) -> Self {
+        ErrKind::ClipboardErr(value)
     }
 }
 
-#[derive(Debug, Default)]
+#[derive(Debug, Default, Clone)]
 struct Config {
     input_file: Option<String>,
     from_clip: bool,
+    /// The encoding of the input and the output, detected from the input if unset.
+    encoding: Option<Encoding>,
     toc: bool,
     title_order: bool,
+    order_style: OrderStyle,
     insert: bool,
+    ids: bool,
+    id_style: IdStyle,
+    /// The revision to list the changed anchors against, empty for the previous one.
+    changed: Option<String>,
+    /// The markup of the input, detected from the file extension if unset.
+    markup: Option<Markup>,
+    slug_style: SlugStyle,
+    levels: LevelRange,
+    format: TocFormat,
+    collapsible: bool,
+    /// Appends the word count and reading time of each section to its TOC entry.
+    toc_stats: bool,
+    /// The title of the TOC, unless the front matter sets one.
+    toc_title: Option<String>,
     output_file: Option<String>,
     to_clip: bool,
+    clipboard: Option<Backend>,
+    in_place: bool,
+    backup: bool,
+    check: bool,
+    /// Keeps processing the input files whenever they change.
+    watch: bool,
+    paths: Vec<String>,
+    index: Option<String>,
+    index_format: IndexFormat,
+    index_sections: bool,
+    lint: bool,
+    links: bool,
+    lint_format: LintFormat,
+    stats: bool,
+    stats_format: LintFormat,
+    /// The heading path of the section to extract.
+    extract: Option<String>,
+    /// The heading level to split at.
+    split: Option<u8>,
+    out_dir: Option<String>,
+    merge: bool,
+    merge_title: Option<String>,
+    shift: u8,
+    print_config: bool,
+    /// The value and source of each setting given on the command line or in a config file.
+    sources: BTreeMap<&'static str, (Value, String)>,
+}
+
+/// An input of a report, labeled by its file name, `stdin` or `clipboard`.
+struct Input {
+    label: String,
+    path: Option<PathBuf>,
+    content: String,
+}
+
+fn main() -> ExitCode {
+    match run() {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(err) => {
+            eprintln!("{err}");
+            err.report()
+        }
+    }
 }
 
-fn main() -> Result<(), ErrKind> {
-    let mut args = std::env::args().skip(1);
-    if args.len() <= 0 {
-        println!("{USAGE}");
+fn run() -> Result<(), ErrKind> {
+    let args = cli::normalize(std::env::args());
+    let lang = i18n::init(&args);
+    let matches = match cli::command(lang).try_get_matches_from(args) {
+        Ok(matches) => matches,
+        Err(err) if !err.use_stderr() || err.kind() == DisplayHelpOnMissingArgumentOrSubcommand => {
+            print!("{}", err.render());
+            return Ok(());
+        }
+        Err(err) => return Err(clap_error(err)),
+    };
+    let mut config = match cli::action(&matches).map_err(clap_error)? {
+        Action::Run(config) => *config,
+        Action::Completions(shell) => {
+            let mut script = Vec::new();
+            clap_complete::generate(shell, &mut cli::command(lang), "mdtoc", &mut script);
+            return write_stdout(&script);
+        }
+        Action::Man => {
+            let mut page = Vec::new();
+            clap_mangen::Man::new(cli::command(lang)).render(&mut page)?;
+            return write_stdout(&page);
+        }
+    };
+
+    if config.input_file.is_none()
+        && config.paths.is_empty()
+        && config.index.is_none()
+        && !config.from_clip
+        && !io::stdin().is_terminal()
+    {
+        config.input_file = Some(STDIO.to_owned());
+    }
+    let cli = config;
+    let path = match cli.input_file.as_deref() {
+        Some(STDIO) | None => cli.paths.first().map(String::as_str),
+        file => file,
+    };
+    let config = configure(&cli, path.map(Path::new))?;
+    if config.print_config {
+        print_config(&config);
         return Ok(());
     }
-    let mut config: Config = Default::default();
-    while let Some(arg) = args.next() {
-        match &arg[..] {
-            "-h" | "--help" => {
-                println!("{USAGE}");
-                return Ok(());
-            }
-            "-v" | "--version" => {
-                println!("{VERSION}");
-                return Ok(());
-            }
-            "-f" | "--file" => {
-                if let Some(file) = args.next() {
-                    config.input_file = Some(file);
-                } else {
-                    return Err(ErrKind::InvalidParam(
-                        "`-f, --file` requires a file param".to_owned(),
-                    ));
-                }
-            }
-            "-fc" | "--from-clip" => config.from_clip = true,
-            "-t" | "--toc" => config.toc = true,
-            "-o" | "--title-order" => config.title_order = true,
-            "-i" | "--insert" => config.insert = true,
-            "-w" | "--write" => {
-                if let Some(file) = args.next() {
-                    config.output_file = Some(file);
-                } else {
-                    return Err(ErrKind::InvalidParam(
-                        "`-w, --write` requires a file param".to_owned(),
-                    ));
-                }
-            }
-            "-tc" | "--to-clip" => config.to_clip = true,
+    verify(&config)?;
+    let mut clipboard = config.clipboard.unwrap_or_else(Backend::detect).open();
+    if config.lint || config.links {
+        return handle_lint(&cli, &config, clipboard.as_mut());
+    }
+    if config.stats {
+        return handle_stats(&cli, &config, clipboard.as_mut());
+    }
+    if let Some(rev) = &config.changed {
+        return handle_changed(&config, rev);
+    }
+    if let Some(path) = &config.extract {
+        return handle_extract(&config, path, clipboard.as_mut());
+    }
+    if let Some(level) = config.split {
+        return handle_split(&config, level, clipboard.as_mut());
+    }
+    if config.merge {
+        return handle_merge(&config, clipboard.as_mut());
+    }
+    if config.watch {
+        return handle_watch(&cli, &config);
+    }
+    if !config.paths.is_empty() {
+        return handle_batch(&cli, &config);
+    }
+    if let Some(dir) = &config.index {
+        return handle_index(&config, dir, clipboard.as_mut());
+    }
+    handle(config, clipboard.as_mut())
+}
+
+fn clap_error(err: clap::Error) -> ErrKind {
+    let msg = err.render().to_string();
+    let msg = msg.trim_start_matches("error: ").trim_end().to_owned();
+    ErrKind::InvalidParam(ParamError::Clap(msg))
+}
+
+/// Applies the settings of the config file found for `path` to the command-line config, except
+/// those given on the command line.
+fn configure(cli: &Config, path: Option<&Path>) -> Result<Config, ErrKind> {
+    let mut config = cli.clone();
+    for setting in
+        project::settings(path).map_err(|err| ErrKind::InvalidParam(ParamError::Config(err)))?
+    {
+        if cli.sources.contains_key(setting.key) {
+            continue;
+        }
+        let flag = setting.value.as_bool().unwrap_or_default();
+        let text = setting.value.as_str().unwrap_or_default();
+        let level = setting.value.as_integer().unwrap_or_default() as u8;
+        match setting.key {
+            "toc" => config.toc = flag,
+            "title-order" => config.title_order = flag,
+            "order-style" => config.order_style = parse_setting(text, &setting.source)?,
+            "insert" => config.insert = flag,
+            "ids" => config.ids = flag,
+            "id-style" => config.id_style = parse_setting(text, &setting.source)?,
+            "slug-style" => config.slug_style = parse_setting(text, &setting.source)?,
+            "min-level" => config.levels.min = level,
+            "max-level" => config.levels.max = level,
+            "format" => config.format = parse_setting(text, &setting.source)?,
+            "collapsible" => config.collapsible = flag,
+            "stats" => config.toc_stats = flag,
+            "toc-title" => config.toc_title = Some(text.to_owned()),
+            "to-clip" => config.to_clip = flag,
+            "clipboard" => config.clipboard = Some(parse_setting(text, &setting.source)?),
+            "encoding" => config.encoding = Some(parse_setting(text, &setting.source)?),
+            "in-place" => config.in_place = flag,
+            "backup" => config.backup = flag,
+            "check" => config.check = flag,
+            "index-format" => config.index_format = parse_setting(text, &setting.source)?,
+            "index-sections" => config.index_sections = flag,
+            "lint-format" => config.lint_format = parse_setting(text, &setting.source)?,
             _ => {}
         }
+        config
+            .sources
+            .insert(setting.key, (setting.value, setting.source));
     }
+    Ok(config)
+}
 
-    verify(&config)?;
-    handle(config)
+/// Parses the text of a setting, naming the config file it is from on error.
+fn parse_setting<T>(text: &str, source: &str) -> Result<T, ErrKind>
+where
+    T: FromStr<Err = mdtoc_core::Error>,
+{
+    text.parse().map_err(|err| {
+        ErrKind::InvalidParam(ParamError::Setting {
+            source: source.to_owned(),
+            err,
+        })
+    })
+}
+
+fn print_config(config: &Config) {
+    for (key, _, default) in project::KEYS {
+        match config.sources.get(key) {
+            Some((value, source)) => println!("{key} = {value} # {source}"),
+            None => println!("{key} = {default} # {}", project::DEFAULT),
+        }
+    }
 }
 
 fn verify(config: &Config) -> Result<(), ErrKind> {
+    if config.levels.min > config.levels.max {
+        return Err(ErrKind::InvalidParam(ParamError::MinAboveMax));
+    }
+    // Reports only read their input, the output settings of a config file do not apply.
+    let report = config.lint || config.links || config.stats;
+    if report && config.from_clip && config.input_file.is_some() {
+        return Err(conflict("`-f, --file`", &["`--from-clip`"]));
+    }
+    if report {
+        return Ok(());
+    }
+    if config.extract.is_some() || config.split.is_some() || config.merge {
+        return verify_sections(config);
+    }
+    if config.changed.is_some() {
+        if config.paths.is_empty() && config.input_file.as_deref().unwrap_or(STDIO) == STDIO {
+            return Err(ErrKind::InvalidParam(ParamError::ChangedRequiresGit));
+        }
+        return Ok(());
+    }
+    if config.check
+        && config.index.is_none()
+        && !config.toc
+        && !config.insert
+        && !config.title_order
+        && !config.ids
+    {
+        return Err(require(
+            "`check`",
+            &[
+                "`-o, --title-order`",
+                "`-i, --insert`",
+                "`-t, --toc`",
+                "`--ids`",
+            ],
+        ));
+    }
+    if config.watch {
+        return verify_watch(config);
+    }
+    if !config.paths.is_empty() {
+        return verify_batch(config);
+    }
+    if config.index.is_some() && (config.input_file.is_some() || config.from_clip) {
+        return Err(conflict(
+            "`index`",
+            &["`-f, --file`", "`--from-clip`", "`-`"],
+        ));
+    }
+    if config.index.is_some() && config.in_place {
+        return Err(conflict("`index`", &["`--in-place`"]));
+    }
     if config.from_clip && config.input_file.is_some() {
-        return Err(ErrKind::InvalidParam(
-            "`-f, --file` conflicts with `-fc, --from-clip`".to_owned(),
+        return Err(conflict("`-f, --file`", &["`--from-clip`"]));
+    }
+    if config.in_place && config.input_file.as_deref().unwrap_or(STDIO) == STDIO {
+        return Err(require("`--in-place`", &["`-f, --file`"]));
+    }
+    if config.in_place && config.output_file.is_some() {
+        return Err(conflict("`--in-place`", &["`-w, --write`"]));
+    }
+    // The TOC alone would replace the document, or be compared with all of it.
+    if config.in_place && config.index.is_none() && !rewrites(config) {
+        return Err(require("`--in-place`", REWRITES));
+    }
+    if config.check && config.index.is_none() && !rewrites(config) {
+        let to_file = config
+            .output_file
+            .as_deref()
+            .is_some_and(|file| file != STDIO);
+        if !to_file {
+            return Err(require(
+                "`check`",
+                &[
+                    "`-w, --write`",
+                    "`-o, --title-order`",
+                    "`-i, --insert`",
+                    "`--ids`",
+                ],
+            ));
+        }
+    }
+    if config.insert && !config.format.is_embeddable() {
+        return Err(require(
+            "`-i, --insert`",
+            &["`--format markdown`", "`--format html`"],
         ));
     }
-    if !config.to_clip && config.output_file.is_none() {
-        return Err(ErrKind::InvalidParam(
-            "`-w, --write` or `-tc, --to-clip` is required".to_owned(),
+    if config.backup && !config.in_place {
+        return Err(require("`--backup`", &["`--in-place`"]));
+    }
+    Ok(())
+}
+
+/// The operations that rewrite the document rather than output its TOC.
+const REWRITES: &[&str] = &["`-o, --title-order`", "`-i, --insert`", "`--ids`"];
+
+fn rewrites(config: &Config) -> bool {
+    config.title_order || config.insert || config.ids
+}
+
+fn conflict(option: &'static str, others: &'static [&'static str]) -> ErrKind {
+    ErrKind::InvalidParam(ParamError::Conflict { option, others })
+}
+
+fn require(option: &'static str, options: &'static [&'static str]) -> ErrKind {
+    ErrKind::InvalidParam(ParamError::Require { option, options })
+}
+
+fn verify_batch(config: &Config) -> Result<(), ErrKind> {
+    if config.input_file.is_some() || config.from_clip {
+        return Err(ErrKind::InvalidParam(ParamError::PathsConflict(&[
+            "`-f, --file`",
+            "`--from-clip`",
+            "`-`",
+        ])));
+    }
+    if config.output_file.is_some() || config.to_clip {
+        return Err(ErrKind::InvalidParam(ParamError::PathsConflict(&[
+            "`-w, --write`",
+            "`--to-clip`",
+        ])));
+    }
+    if !config.in_place && !config.check {
+        return Err(ErrKind::InvalidParam(ParamError::PathsRequire(&[
+            "`--in-place`",
+            "`--check`",
+        ])));
+    }
+    if !rewrites(config) {
+        return Err(ErrKind::InvalidParam(ParamError::PathsRequire(REWRITES)));
+    }
+    Ok(())
+}
+
+/// Watching processes files like a batch, the input file included.
+fn verify_watch(config: &Config) -> Result<(), ErrKind> {
+    if config.from_clip || config.input_file.as_deref() == Some(STDIO) {
+        return Err(conflict("`--watch`", &["`--from-clip`", "`-`"]));
+    }
+    if config.input_file.is_none() && config.paths.is_empty() {
+        return Err(require("`--watch`", &["`-f, --file`", "`PATH`"]));
+    }
+    if config.output_file.is_some() || config.to_clip {
+        return Err(conflict("`--watch`", &["`-w, --write`", "`--to-clip`"]));
+    }
+    if !config.in_place && !config.check {
+        return Err(require("`--watch`", &["`--in-place`", "`--check`"]));
+    }
+    if !rewrites(config) {
+        return Err(require("`--watch`", REWRITES));
+    }
+    Ok(())
+}
+
+/// Extract, split and merge only write their output, the `in-place` and `check` settings of a
+/// config file do not apply, except `in-place` for the index of `split`.
+fn verify_sections(config: &Config) -> Result<(), ErrKind> {
+    let command = match (&config.extract, config.split) {
+        (Some(_), _) => "extract",
+        (_, Some(_)) => "split",
+        _ => "merge",
+    };
+    if config.merge && (config.input_file.is_some() || config.from_clip) {
+        return Err(conflict(
+            "`merge`",
+            &["`-f, --file`", "`--from-clip`", "`-`"],
         ));
     }
+    if !config.merge && !config.paths.is_empty() {
+        return Err(ErrKind::InvalidParam(ParamError::SingleInput(command)));
+    }
+    if config.from_clip && config.input_file.is_some() {
+        return Err(conflict("`-f, --file`", &["`--from-clip`"]));
+    }
+    if config.split.is_some() && config.in_place {
+        if config.input_file.as_deref().unwrap_or(STDIO) == STDIO {
+            return Err(require("`--in-place`", &["`-f, --file`"]));
+        }
+        if config.output_file.is_some() {
+            return Err(conflict("`--in-place`", &["`-w, --write`"]));
+        }
+    }
     Ok(())
 }
 
-fn handle(config: Config) -> Result<(), ErrKind> {
-    let content = read_markdown(config)?;
-    for line in content {
-        let line = line?;
-        println!("{line}");
+fn handle_batch(cli: &Config, config: &Config) -> Result<(), ErrKind> {
+    let files = batch::collect_files(&config.paths, &markup::EXTENSIONS)
+        .map_err(|err| ErrKind::InvalidParam(ParamError::Config(err)))?;
+    let outcomes = batch::run_parallel(&files, |file| {
+        match configure(cli, Some(file)).and_then(|config| handle_file(&config, file)) {
+            Ok(outcome) => outcome,
+            Err(err) => Outcome::Failed(err.to_string()),
+        }
+    });
+    let summary = batch::report(&files, &outcomes);
+    if summary.failed > 0 {
+        Err(ErrKind::IoErr(IoError::FilesFailed(summary.failed)))
+    } else if config.check && summary.changed > 0 {
+        Err(ErrKind::CheckFailed(CheckError::FilesOutOfDate(
+            summary.changed,
+        )))
+    } else {
+        Ok(())
+    }
+}
+
+/// Processes the files, then again whenever they change, until interrupted. Failures are
+/// reported without stopping, and the writes of the watch itself are not taken for changes.
+fn handle_watch(cli: &Config, config: &Config) -> Result<(), ErrKind> {
+    let mut paths = config.paths.clone();
+    paths.extend(config.input_file.clone());
+    let mut watcher = watch::Watcher::new(paths, &markup::EXTENSIONS, watch::DEBOUNCE);
+    let mut last_error = None;
+    loop {
+        match watcher.poll() {
+            Ok(files) => {
+                for file in files {
+                    let (outcome, written) = configure(cli, Some(&file))
+                        .and_then(|config| rewrite_file(&config, &file))
+                        .unwrap_or_else(|err| (Outcome::Failed(err.to_string()), None));
+                    if let Some(metadata) = written {
+                        watcher.written(&file, &metadata);
+                    }
+                    if outcome != Outcome::Unchanged {
+                        batch::print_outcome(&file, &outcome);
+                    }
+                }
+                last_error = None;
+            }
+            // A watched path may be missing for a moment, such as while an editor replaces it.
+            Err(err) if last_error.as_ref() != Some(&err) => {
+                eprintln!("{}", ErrKind::InvalidParam(ParamError::Config(err.clone())));
+                last_error = Some(err);
+            }
+            Err(_) => {}
+        }
+        std::thread::sleep(watch::POLL_INTERVAL);
+    }
+}
+
+fn handle_index(config: &Config, dir: &str, clipboard: &mut dyn Clipboard) -> Result<(), ErrKind> {
+    let output = config
+        .output_file
+        .as_deref()
+        .and_then(|file| fs::canonicalize(file).ok());
+    let files: Vec<_> = batch::collect_files(&[dir.to_owned()], &MARKDOWN_EXTENSIONS)
+        .map_err(|err| ErrKind::InvalidParam(ParamError::Config(err)))?
+        .into_iter()
+        .filter(|file| output.is_none() || fs::canonicalize(file).ok() != output)
+        .collect();
+    let options = IndexOptions {
+        format: config.index_format,
+        sections: config.index_sections,
+        slug_style: config.slug_style,
+    };
+    let mut index = index::generate_index(Path::new(dir), &files, &options)?;
+    index.push(String::new());
+    let index = index.join("\n");
+    if config.check {
+        return check_result(config, "", &index);
+    }
+    write_result(config, clipboard, &index)
+}
+
+/// Lints the heading structure, or checks the links with `links`, and prints a report.
+fn handle_lint(
+    cli: &Config,
+    config: &Config,
+    clipboard: &mut dyn Clipboard,
+) -> Result<(), ErrKind> {
+    let (inputs, failed) = read_inputs(config, clipboard, &MARKDOWN_EXTENSIONS)?;
+    let mut checker = LinkChecker::new(config.slug_style);
+    let mut problems = 0;
+    let mut report = Vec::new();
+    for Input {
+        label: file,
+        path,
+        content,
+    } in &inputs
+    {
+        let lines = Text::parse(content).lines;
+        let diagnostics = if config.links {
+            checker.check(path.as_deref(), &lines)
+        } else {
+            let config = match path {
+                Some(path) => configure(cli, Some(path))?,
+                None => config.clone(),
+            };
+            let title_order = config.title_order.then(|| {
+                let levels = front_matter::file_options(&lines).levels(config.levels);
+                (config.order_style, levels)
+            });
+            lint::lint(&lines, title_order)
+        };
+        problems += diagnostics.len();
+        match config.lint_format {
+            LintFormat::Text => report.extend(lint::render_text(file, &diagnostics)),
+            LintFormat::Json => report.extend(
+                lint::to_json(file, &diagnostics)
+                    .iter()
+                    .map(|value| value.to_string()),
+            ),
+        }
+    }
+    let report = match config.lint_format {
+        LintFormat::Text => report.join("\n"),
+        LintFormat::Json => format!("[{}]", report.join(",\n")),
+    };
+    if !report.is_empty() {
+        println!("{report}");
+    }
+
+    if failed > 0 {
+        Err(ErrKind::IoErr(IoError::FilesFailed(failed)))
+    } else if problems > 0 {
+        Err(ErrKind::LintFailed { problems })
+    } else {
+        Ok(())
+    }
+}
+
+/// Prints the statistics of each section, aged by `git blame` for files in a repository.
+fn handle_stats(
+    cli: &Config,
+    config: &Config,
+    clipboard: &mut dyn Clipboard,
+) -> Result<(), ErrKind> {
+    let (inputs, failed) = read_inputs(config, clipboard, &markup::EXTENSIONS)?;
+    let now = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |now| now.as_secs());
+    let mut reports = Vec::new();
+    for Input {
+        label: file,
+        path,
+        content,
+    } in &inputs
+    {
+        let config = match path {
+            Some(path) => configure(cli, Some(path))?,
+            None => config.clone(),
+        };
+        let markup = match (config.markup, path) {
+            (Some(markup), _) => markup,
+            (None, Some(path)) => Markup::detect(path),
+            _ => Markup::Markdown,
+        };
+        let document = Document::parse_markup(content, markup).map_err(|err| {
+            ErrKind::InvalidParam(ParamError::Document {
+                file: Some(file.clone()),
+                err,
+            })
+        })?;
+        // The lines of a notebook are those of its markdown cells, not of the file.
+        let times = match path {
+            Some(path) if markup != Markup::Notebook => stats::blame_times(path).ok(),
+            _ => None,
+        };
+        let rows = stats::section_rows(
+            &document.headings(),
+            &document.section_stats(),
+            document.file_options().levels(config.levels),
+            times.as_deref(),
+            now,
+            document.lines().len(),
+        );
+        reports.push(match config.stats_format {
+            LintFormat::Text => stats::render_text(file, &rows).join("\n"),
+            LintFormat::Json => stats::to_json(file, &rows).to_string(),
+        });
+    }
+    match config.stats_format {
+        LintFormat::Text => println!("{}", reports.join("\n\n")),
+        LintFormat::Json => println!("[{}]", reports.join(",\n")),
+    }
+    if failed > 0 {
+        return Err(ErrKind::IoErr(IoError::FilesFailed(failed)));
+    }
+    Ok(())
+}
+
+/// Reads the input of a report: the single input, or the files with `extensions` among the
+/// paths. Returns the inputs, and the number of files that could not be read.
+fn read_inputs(
+    config: &Config,
+    clipboard: &mut dyn Clipboard,
+    extensions: &[&str],
+) -> Result<(Vec<Input>, usize), ErrKind> {
+    let mut inputs = Vec::new();
+    let mut failed = 0;
+    if config.paths.is_empty() {
+        let (label, path) = match config.input_file.as_deref() {
+            Some(STDIO) => ("stdin", None),
+            Some(file) => (file, Some(PathBuf::from(file))),
+            None => ("clipboard", None),
+        };
+        let (content, _) = read_markdown(config, clipboard)?;
+        inputs.push(Input {
+            label: label.to_owned(),
+            path,
+            content,
+        });
+    } else {
+        for file in batch::collect_files(&config.paths, extensions)
+            .map_err(|err| ErrKind::InvalidParam(ParamError::Config(err)))?
+        {
+            match read_file(&file, config.encoding) {
+                Ok((content, _)) => inputs.push(Input {
+                    label: file.display().to_string(),
+                    path: Some(file),
+                    content,
+                }),
+                Err(err) => {
+                    eprintln!("{}: {err}", file.display());
+                    failed += 1;
+                }
+            }
+        }
+    }
+    Ok((inputs, failed))
+}
+
+fn handle_extract(
+    config: &Config,
+    path: &str,
+    clipboard: &mut dyn Clipboard,
+) -> Result<(), ErrKind> {
+    let (content, encoding) = read_markdown(config, clipboard)?;
+    let text = Text::parse(&content);
+    let Some(section) = sections::find_section(&text.lines, path) else {
+        return Err(ErrKind::InvalidParam(ParamError::NoSection(
+            path.to_owned(),
+        )));
+    };
+    let config = Config {
+        in_place: false,
+        encoding: Some(encoding),
+        ..config.clone()
+    };
+    write_result(&config, clipboard, &text.render(&text.lines[section]))
+}
+
+/// Writes the chapters into the output directory and the index to the output.
+fn handle_split(config: &Config, level: u8, clipboard: &mut dyn Clipboard) -> Result<(), ErrKind> {
+    let (content, encoding) = read_markdown(config, clipboard)?;
+    let config = &Config {
+        encoding: Some(encoding),
+        ..config.clone()
+    };
+    let text = Text::parse(&content);
+    let input = config.input_file.as_deref().filter(|file| *file != STDIO);
+    let source_dir = input
+        .and_then(|file| Path::new(file).parent())
+        .map_or_else(PathBuf::new, Path::to_path_buf);
+    let out_dir = config
+        .out_dir
+        .as_ref()
+        .map_or_else(|| source_dir.clone(), PathBuf::from);
+    let index_file = match config.output_file.as_deref() {
+        Some(STDIO) | None => input.unwrap_or("README.md"),
+        Some(file) => file,
+    };
+    let options = SplitOptions {
+        level,
+        slug_style: config.slug_style,
+        source_dir,
+        out_dir: out_dir.clone(),
+        index_file: PathBuf::from(index_file),
+    };
+    let (index, chapters) = sections::split(&text.lines, &options).map_err(|err| {
+        let file = PathBuf::from(config.input_file.as_deref().unwrap_or(STDIO));
+        ErrKind::InvalidParam(ParamError::Shift(ShiftError { file, ..err }))
+    })?;
+    if chapters.is_empty() {
+        return Err(ErrKind::InvalidParam(ParamError::NoSplitLevel(level)));
+    }
+    fs::create_dir_all(&out_dir)?;
+    for chapter in &chapters {
+        let content = encode(config, &text.render(&chapter.lines))?;
+        fs::write(out_dir.join(&chapter.file), content)?;
+    }
+    write_result(config, clipboard, &text.render(&index))
+}
+
+fn handle_merge(config: &Config, clipboard: &mut dyn Clipboard) -> Result<(), ErrKind> {
+    let mut documents = Vec::new();
+    let mut encoding = config.encoding;
+    for file in &config.paths {
+        let (content, detected) = read_file(Path::new(file), config.encoding)
+            .map_err(|err| ErrKind::io(Some(file), err))?;
+        encoding.get_or_insert(detected);
+        documents.push((PathBuf::from(file), Text::parse(&content).lines));
+    }
+    let options = MergeOptions {
+        title: config.merge_title.clone(),
+        shift: config.shift,
+        slug_style: config.slug_style,
+        out_dir: config
+            .output_file
+            .as_deref()
+            .filter(|file| *file != STDIO)
+            .and_then(|file| Path::new(file).parent())
+            .map_or_else(PathBuf::new, Path::to_path_buf),
+    };
+    let mut merged = sections::merge(&documents, &options)
+        .map_err(|err| ErrKind::InvalidParam(ParamError::Shift(err)))?;
+    merged.push(String::new());
+    let config = Config {
+        in_place: false,
+        encoding,
+        ..config.clone()
+    };
+    write_result(&config, clipboard, &merged.join("\n"))
+}
+
+/// Lists the heading anchors that changed since the previous git revision of each file.
+fn handle_changed(config: &Config, rev: &str) -> Result<(), ErrKind> {
+    let files = match config.input_file.as_deref() {
+        Some(file) if config.paths.is_empty() => vec![PathBuf::from(file)],
+        _ => batch::collect_files(&config.paths, &MARKDOWN_EXTENSIONS)
+            .map_err(|err| ErrKind::InvalidParam(ParamError::Config(err)))?,
+    };
+    let rev = Some(rev).filter(|rev| !rev.is_empty());
+    for file in files {
+        let label = file.display().to_string();
+        let (current, encoding) = read_file(&file, config.encoding)?;
+        let previous = changed::previous_revision(&file, rev).map_err(|err| {
+            ErrKind::IoErr(IoError::Git {
+                file: label.clone(),
+                err,
+            })
+        })?;
+        let previous = encoding
+            .decode(&previous)
+            .map_err(|err| ErrKind::encoding(Some(&label), err))?;
+        let diagnostics = changed::changed_anchors(
+            &Text::parse(&previous).lines,
+            &Text::parse(&current).lines,
+            config.slug_style,
+        );
+        for line in lint::render_text(&label, &diagnostics) {
+            println!("{line}");
+        }
     }
     Ok(())
 }
 
+fn handle_file(config: &Config, file: &Path) -> Result<Outcome, ErrKind> {
+    rewrite_file(config, file).map(|(outcome, _)| outcome)
+}
+
+/// Processes a file like `handle_file`, also returning the metadata of the file it wrote.
+fn rewrite_file(config: &Config, file: &Path) -> Result<(Outcome, Option<fs::Metadata>), ErrKind> {
+    let (content, encoding) = read_file(file, config.encoding)?;
+    let markup = config.markup.unwrap_or_else(|| Markup::detect(file));
+    let result = process(config, markup, &content)?;
+    if config.check {
+        let label = file.display().to_string();
+        return Ok(match check::unified_diff(&content, &result, &label) {
+            Some(diff) => (Outcome::Changed(Some(diff)), None),
+            None => (Outcome::Unchanged, None),
+        });
+    }
+    if content == result {
+        return Ok((Outcome::Unchanged, None));
+    }
+    let result = encoding
+        .encode(&result)
+        .map_err(|err| ErrKind::encoding(None, err))?;
+    let metadata = in_place::write_in_place(&file.to_string_lossy(), &result, config.backup)?;
+    Ok((Outcome::Changed(None), Some(metadata)))
+}
+
+fn handle(config: Config, clipboard: &mut dyn Clipboard) -> Result<(), ErrKind> {
+    let (content, encoding) = read_markdown(&config, clipboard)?;
+    let config = Config {
+        encoding: Some(encoding),
+        ..config
+    };
+    let markup = match (config.markup, config.input_file.as_deref()) {
+        (Some(markup), _) => markup,
+        (None, Some(file)) if file != STDIO => Markup::detect(Path::new(file)),
+        _ => Markup::Markdown,
+    };
+    let result = process(&config, markup, &content)?;
+    if config.check {
+        return check_result(&config, &content, &result);
+    }
+    write_result(&config, clipboard, &result)
+}
+
+/// Transforms a document of any markup. With `--toc` alone, only the TOC is output.
+fn process(config: &Config, markup: Markup, content: &str) -> Result<String, ErrKind> {
+    let mut document = Document::parse_markup(content, markup)
+        .map_err(|err| ErrKind::InvalidParam(ParamError::Document { file: None, err }))?;
+    let file_options = document.file_options();
+    let levels = file_options.levels(config.levels);
+    if config.title_order {
+        document.apply_numbering(config.order_style, levels);
+    }
+    if config.ids {
+        document.add_ids(config.id_style, config.slug_style, levels);
+    }
+    // HTML and OPML have a title even if none is set, in the language of the messages.
+    let title = file_options.title.or_else(|| config.toc_title.clone());
+    let title = title.or_else(|| {
+        matches!(config.format, TocFormat::Html | TocFormat::Opml)
+            .then(|| i18n::toc_title(i18n::lang()).to_owned())
+    });
+    let options = TocOptions {
+        slug_style: config.slug_style,
+        format: config.format,
+        collapsible: config.collapsible,
+        levels,
+        title,
+        stats: config.toc_stats,
+    };
+    if config.insert {
+        document.insert_toc(&options);
+    } else if config.toc {
+        return Ok(document.toc(&options));
+    }
+    Ok(document.render())
+}
+
+/// Reads the input, and returns it with the encoding it is written back in.
 fn read_markdown(
-    config: Config,
-) -> Result<Box<dyn Iterator<Item = Result<String, io::Error>>>, ErrKind> {
+    config: &Config,
+    clipboard: &mut dyn Clipboard,
+) -> Result<(String, Encoding), ErrKind> {
     if config.from_clip {
-        let cursor = match clipboard_win::get_clipboard_string() {
-            Ok(content) => Cursor::new(content),
-            Err(err) => {
-                return Err(ErrKind::from(err));
-            }
+        Ok((clipboard.get()?, config.encoding.unwrap_or_default()))
+    } else if let Some(file) = &config.input_file {
+        if file == STDIO {
+            let mut bytes = Vec::new();
+            io::stdin().read_to_end(&mut bytes)?;
+            let encoding = config.encoding.unwrap_or_else(|| Encoding::detect(&bytes));
+            let content = encoding
+                .decode(&bytes)
+                .map_err(|err| ErrKind::encoding(Some("stdin"), err))?;
+            Ok((content, encoding))
+        } else {
+            read_file(Path::new(file), config.encoding).map_err(|err| ErrKind::io(Some(file), err))
+        }
+    } else {
+        Err(ErrKind::InvalidParam(ParamError::NoInput))
+    }
+}
+
+fn write_result(
+    config: &Config,
+    clipboard: &mut dyn Clipboard,
+    content: &str,
+) -> Result<(), ErrKind> {
+    let bytes = encode(config, content)?;
+    match config.output_file.as_deref() {
+        Some(STDIO) => write_stdout(&bytes)?,
+        Some(file) => fs::write(file, &bytes)?,
+        None if !config.in_place && !config.to_clip => write_stdout(&bytes)?,
+        None => {}
+    }
+    if let (true, Some(file)) = (config.in_place, &config.input_file) {
+        in_place::write_in_place(file, &bytes, config.backup)?;
+    }
+    if config.to_clip {
+        clipboard.set(content)?;
+    }
+    Ok(())
+}
+
+/// Writes the output to stdout. A reader that stops early, such as `head`, closes the pipe, which
+/// ends the output rather than failing.
+fn write_stdout(bytes: &[u8]) -> Result<(), ErrKind> {
+    match io::stdout().write_all(bytes) {
+        Err(err) if err.kind() == io::ErrorKind::BrokenPipe => Ok(()),
+        result => Ok(result?),
+    }
+}
+
+/// Encodes the output in `--encoding`, which is set to that of the input after reading it.
+fn encode(config: &Config, content: &str) -> Result<Vec<u8>, ErrKind> {
+    let encoding = config.encoding.unwrap_or_default();
+    encoding
+        .encode(content)
+        .map_err(|err| ErrKind::encoding(None, err))
+}
+
+fn check_result(config: &Config, original: &str, result: &str) -> Result<(), ErrKind> {
+    let (label, current) = match config.output_file.as_deref() {
+        Some(file) if file != STDIO => {
+            let current = read_file(Path::new(file), config.encoding).map(|(current, _)| current);
+            (file, current.unwrap_or_default())
+        }
+        _ => {
+            let label = match config.input_file.as_deref() {
+                Some(STDIO) => "stdin",
+                Some(file) => file,
+                None => "clipboard",
+            };
+            (label, original.to_owned())
+        }
+    };
+    match check::unified_diff(&current, result, label) {
+        Some(diff) => {
+            print!("{diff}");
+            Err(ErrKind::CheckFailed(CheckError::OutOfDate(
+                label.to_owned(),
+            )))
+        }
+        None => Ok(()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use clipboard::MemoryClipboard;
+
+    #[test]
+    fn test_handle_clipboard() {
+        let mut clipboard = MemoryClipboard {
+            content: Some("# A\n## B".to_owned()),
         };
-        Ok(Box::new(cursor.lines()))
-    } else if let Some(file) = config.input_file {
-        let reader = match File::open(file) {
-            Ok(file) => BufReader::new(file),
-            Err(err) => {
-                return Err(ErrKind::from(err));
-            }
+        let config = Config {
+            from_clip: true,
+            to_clip: true,
+            toc: true,
+            ..Default::default()
         };
-        Ok(Box::new(reader.lines()))
-    } else {
-        Err(ErrKind::InvalidParam(
-            "`-f, --file` or `-fc, --from-clip` is required".to_owned(),
-        ))
+        assert!(handle(config, &mut clipboard).is_ok());
+        assert_eq!(Some("- [A](#a)\n  - [B](#b)".to_owned()), clipboard.content);
+    }
+
+    #[test]
+    fn test_verify_toc_in_place() {
+        let config = Config {
+            toc: true,
+            in_place: true,
+            input_file: Some("a.md".to_owned()),
+            ..Default::default()
+        };
+        assert!(matches!(
+            verify(&config),
+            Err(ErrKind::InvalidParam(ParamError::Require { .. }))
+        ));
+        let config = Config {
+            insert: true,
+            ..config
+        };
+        assert!(verify(&config).is_ok());
+
+        let config = Config {
+            toc: true,
+            check: true,
+            paths: vec!["docs".to_owned()],
+            ..Default::default()
+        };
+        assert!(matches!(
+            verify(&config),
+            Err(ErrKind::InvalidParam(ParamError::PathsRequire(REWRITES)))
+        ));
+
+        let config = Config {
+            toc: true,
+            in_place: true,
+            watch: true,
+            input_file: Some("a.md".to_owned()),
+            ..Default::default()
+        };
+        assert!(matches!(
+            verify(&config),
+            Err(ErrKind::InvalidParam(ParamError::Require { .. }))
+        ));
+    }
+
+    #[test]
+    fn test_process_keep_line_endings() {
+        let config = Config {
+            title_order: true,
+            insert: true,
+            ..Default::default()
+        };
+        let expected =
+            "# 1 A\r\n\r\n<!-- toc -->\r\n- [1 A](#1-a)\r\n<!-- tocstop -->\r\n\r\ntext\r\n";
+        assert_eq!(
+            expected,
+            process(&config, Markup::Markdown, "# A\r\ntext\r\n").unwrap()
+        );
+        assert_eq!(
+            expected,
+            process(&config, Markup::Markdown, expected).unwrap()
+        );
     }
 }
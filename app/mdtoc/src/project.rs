@@ -0,0 +1,212 @@
+use globset::GlobBuilder;
+use std::fs;
+use std::path::{Path, PathBuf};
+use toml::{Table, Value};
+
+pub const CONFIG_FILE: &str = ".mdtoc.toml";
+/// A config shared with other tools, holding the settings in a `[mdtoc]` table.
+pub const SHARED_CONFIG_FILE: &str = ".config.toml";
+const SHARED_TABLE: &str = "mdtoc";
+const OVERRIDES: &str = "overrides";
+pub const COMMAND_LINE: &str = "command line";
+pub const DEFAULT: &str = "default";
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Kind {
+    Bool,
+    Str,
+    Level,
+}
+
+/// The settings of a config file: each key mirrors a command-line option, with its kind and
+/// default value.
+pub const KEYS: [(&str, Kind, &str); 22] = [
+    ("toc", Kind::Bool, "false"),
+    ("title-order", Kind::Bool, "false"),
+    ("order-style", Kind::Str, "\"decimal\""),
+    ("insert", Kind::Bool, "false"),
+    ("ids", Kind::Bool, "false"),
+    ("id-style", Kind::Str, "\"anchor\""),
+    ("slug-style", Kind::Str, "\"github\""),
+    ("min-level", Kind::Level, "1"),
+    ("max-level", Kind::Level, "6"),
+    ("format", Kind::Str, "\"markdown\""),
+    ("collapsible", Kind::Bool, "false"),
+    ("stats", Kind::Bool, "false"),
+    ("toc-title", Kind::Str, "\"none\""),
+    ("to-clip", Kind::Bool, "false"),
+    ("clipboard", Kind::Str, "\"detected\""),
+    ("encoding", Kind::Str, "\"detected\""),
+    ("in-place", Kind::Bool, "false"),
+    ("backup", Kind::Bool, "false"),
+    ("check", Kind::Bool, "false"),
+    ("index-format", Kind::Str, "\"markdown\""),
+    ("index-sections", Kind::Bool, "false"),
+    ("lint-format", Kind::Str, "\"text\""),
+];
+
+/// A setting read from a config file.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Setting {
+    pub key: &'static str,
+    pub value: Value,
+    pub source: String,
+}
+
+/// Finds the nearest config file in the directory of `path` or its ancestors, starting from the
+/// working directory if `path` is `None`, and returns the settings that apply to `path`: the
+/// top-level ones followed by those of every `[[overrides]]` whose `glob` matches, so that later
+/// settings take precedence.
+pub fn settings(path: Option<&Path>) -> Result<Vec<Setting>, String> {
+    let start = match path {
+        Some(path) => std::path::absolute(path).map_err(|err| err.to_string())?,
+        None => std::env::current_dir().map_err(|err| err.to_string())?,
+    };
+    let Some((file, table)) = find(&start)? else {
+        return Ok(Vec::new());
+    };
+    let source = file.display().to_string();
+    let mut settings = read_table(&table, &source, OVERRIDES)?;
+    let Some(overrides) = table.get(OVERRIDES) else {
+        return Ok(settings);
+    };
+    let Some(overrides) = overrides.as_array() else {
+        return Err(format!(
+            "{source}: `{OVERRIDES}` must be an array of tables"
+        ));
+    };
+    let dir = file.parent().unwrap_or(Path::new(""));
+    let relative = path.and_then(|_| start.strip_prefix(dir).ok());
+    for table in overrides {
+        let Some(table) = table.as_table() else {
+            return Err(format!(
+                "{source}: `{OVERRIDES}` must be an array of tables"
+            ));
+        };
+        let Some(glob) = table.get("glob").and_then(Value::as_str) else {
+            return Err(format!("{source}: every `{OVERRIDES}` requires a `glob`"));
+        };
+        let matcher = GlobBuilder::new(glob)
+            .literal_separator(true)
+            .build()
+            .map_err(|err| format!("{source}: invalid glob `{glob}`: {err}"))?
+            .compile_matcher();
+        if relative.is_some_and(|relative| matcher.is_match(relative)) {
+            let source = format!("{source} ({OVERRIDES} `{glob}`)");
+            settings.extend(read_table(table, &source, "glob")?);
+        }
+    }
+    Ok(settings)
+}
+
+fn find(start: &Path) -> Result<Option<(PathBuf, Table)>, String> {
+    for dir in start.ancestors() {
+        let file = dir.join(CONFIG_FILE);
+        if file.is_file() {
+            return Ok(Some((file.clone(), parse(&file)?)));
+        }
+        let file = dir.join(SHARED_CONFIG_FILE);
+        if file.is_file() {
+            if let Some(Value::Table(table)) = parse(&file)?.remove(SHARED_TABLE) {
+                return Ok(Some((file, table)));
+            }
+        }
+    }
+    Ok(None)
+}
+
+fn parse(file: &Path) -> Result<Table, String> {
+    fs::read_to_string(file)
+        .map_err(|err| format!("{}: {err}", file.display()))?
+        .parse()
+        .map_err(|err| format!("{}: {err}", file.display()))
+}
+
+/// Reads the settings of a table, skipping the `skip` key.
+fn read_table(table: &Table, source: &str, skip: &str) -> Result<Vec<Setting>, String> {
+    let mut settings = Vec::new();
+    for (key, value) in table {
+        if key == skip {
+            continue;
+        }
+        let name = key.replace('_', "-");
+        let Some((key, kind, _)) = KEYS.iter().find(|(key, _, _)| *key == name) else {
+            return Err(format!("{source}: unknown setting `{key}`"));
+        };
+        let valid = match kind {
+            Kind::Bool => value.is_bool(),
+            Kind::Str => value.is_str(),
+            Kind::Level => value
+                .as_integer()
+                .is_some_and(|level| (1..=6).contains(&level)),
+        };
+        if !valid {
+            let expected = match kind {
+                Kind::Bool => "a boolean",
+                Kind::Str => "a string",
+                Kind::Level => "a level between 1 and 6",
+            };
+            return Err(format!("{source}: `{key}` requires {expected}"));
+        }
+        settings.push(Setting {
+            key,
+            value: value.clone(),
+            source: source.to_owned(),
+        });
+    }
+    Ok(settings)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn keys(settings: &[Setting]) -> Vec<(&str, String)> {
+        settings
+            .iter()
+            .map(|setting| (setting.key, setting.value.to_string()))
+            .collect()
+    }
+
+    #[test]
+    fn test_settings() {
+        let root = tempfile::tempdir().unwrap();
+        let docs = root.path().join("docs/zh");
+        fs::create_dir_all(&docs).unwrap();
+        fs::write(
+            root.path().join(CONFIG_FILE),
+            concat!(
+                "toc = true\nslug_style = \"gitlab\"\nmax-level = 3\n",
+                "[[overrides]]\nglob = \"docs/zh/*.md\"\norder-style = \"chinese\"\nmax-level = 2\n",
+                "[[overrides]]\nglob = \"*.md\"\nformat = \"html\"\n",
+            ),
+        )
+        .unwrap();
+        let settings = settings(Some(&docs.join("a.md"))).unwrap();
+        let expected = vec![
+            ("max-level", "3".to_owned()),
+            ("slug-style", "\"gitlab\"".to_owned()),
+            ("toc", "true".to_owned()),
+            ("max-level", "2".to_owned()),
+            ("order-style", "\"chinese\"".to_owned()),
+        ];
+        assert_eq!(expected, keys(&settings));
+        assert!(settings[4].source.ends_with("(overrides `docs/zh/*.md`)"));
+    }
+
+    #[test]
+    fn test_shared_settings() {
+        let root = tempfile::tempdir().unwrap();
+        fs::write(
+            root.path().join(SHARED_CONFIG_FILE),
+            "[other]\nkey = 1\n[mdtoc]\ninsert = true\n",
+        )
+        .unwrap();
+        let settings = settings(Some(&root.path().join("a.md"))).unwrap();
+        assert_eq!(vec![("insert", "true".to_owned())], keys(&settings));
+
+        fs::write(root.path().join(CONFIG_FILE), "min-level = 7").unwrap();
+        let err = super::settings(Some(&root.path().join("a.md"))).unwrap_err();
+        assert!(err.ends_with("`min-level` requires a level between 1 and 6"));
+    }
+}
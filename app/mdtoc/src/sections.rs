@@ -0,0 +1,546 @@
+use crate::links::{is_external, rewrite_links};
+use mdtoc_core::heading::{front_matter_end, parse_headings, Heading, HeadingKind};
+use mdtoc_core::slug::{plain_text, SlugStyle, Slugger};
+use std::collections::HashMap;
+use std::fs;
+use std::ops::Range;
+use std::path::{Component, Path, PathBuf};
+
+/// A file written by [`split`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct Chapter {
+    pub file: String,
+    pub title: String,
+    pub lines: Vec<String>,
+}
+
+/// A heading that a shift would move above H1 or below H6.
+#[derive(Debug, PartialEq)]
+pub struct ShiftError {
+    /// The file of the heading, which is empty for the input of [`split`].
+    pub file: PathBuf,
+    pub line: usize,
+    pub level: u8,
+    pub shifted: i8,
+}
+
+pub struct SplitOptions {
+    /// Every heading of this level starts a chapter.
+    pub level: u8,
+    pub slug_style: SlugStyle,
+    /// The directory of the document, which its relative links are resolved from.
+    pub source_dir: PathBuf,
+    /// The directory the chapters are written to.
+    pub out_dir: PathBuf,
+    /// The file the index is written to.
+    pub index_file: PathBuf,
+}
+
+pub struct MergeOptions {
+    /// The H1 heading the merged documents are nested under.
+    pub title: Option<String>,
+    /// The number of levels every heading is moved down.
+    pub shift: u8,
+    pub slug_style: SlugStyle,
+    /// The directory of the merged document, which other relative links are rebased on.
+    pub out_dir: PathBuf,
+}
+
+/// Finds the section at a heading path such as `Install > Linux`, where every title names a
+/// heading nested in the section of the previous one. Titles are compared as plain text,
+/// ignoring case. Returns the lines from the heading to the next heading of the same or a
+/// higher level, without trailing blank lines.
+pub fn find_section(lines: &[String], path: &str) -> Option<Range<usize>> {
+    if path.trim().is_empty() {
+        return None;
+    }
+    let headings = parse_headings(lines);
+    let mut search = 0..lines.len();
+    let mut section = None;
+    for title in path.split('>').map(str::trim) {
+        let index = headings
+            .iter()
+            .position(|heading| search.contains(&heading.line) && same_title(heading, title))?;
+        let end = section_end(&headings, index, lines.len());
+        search = headings[index].end_line() + 1..end;
+        section = Some(headings[index].line..end);
+    }
+    section.map(|section| section.start..trim_blank_end(lines, section.end))
+}
+
+/// Moves every heading by `shift` levels, failing if one would leave 1 to 6. Setext headings that
+/// change level are rewritten as ATX headings.
+pub fn shift_levels(lines: &[String], shift: i8) -> Result<Vec<String>, ShiftError> {
+    let headings = parse_headings(lines);
+    if let Some(heading) = headings
+        .iter()
+        .find(|heading| !(1..=6).contains(&(heading.level as i8 + shift)))
+    {
+        return Err(ShiftError {
+            file: PathBuf::new(),
+            line: heading.line + 1,
+            level: heading.level,
+            shifted: heading.level as i8 + shift,
+        });
+    }
+    let mut shifted = lines.to_vec();
+    let mut removed = Vec::new();
+    for heading in headings {
+        let level = (heading.level as i8 + shift) as u8;
+        if level == heading.level {
+            continue;
+        }
+        let hashes = "#".repeat(level as usize);
+        match heading.kind {
+            HeadingKind::Atx => {
+                shifted[heading.line] = format!("{hashes} {}", heading.title(lines))
+                    .trim_end()
+                    .to_owned();
+            }
+            HeadingKind::Setext { underline } => {
+                let title: Vec<&str> = lines[heading.line..underline]
+                    .iter()
+                    .map(|line| line.trim())
+                    .collect();
+                shifted[heading.line] = format!("{hashes} {}", title.join(" "));
+                removed.extend(heading.line + 1..=underline);
+            }
+            HeadingKind::Html => {
+                shifted[heading.line] = relevel_html(&lines[heading.line], heading.level, level);
+            }
+            // Only markdown headings are parsed here.
+            HeadingKind::AsciiDoc | HeadingKind::Rst { .. } | HeadingKind::Org => {}
+        }
+    }
+    Ok(shifted
+        .into_iter()
+        .enumerate()
+        .filter(|(index, _)| !removed.contains(index))
+        .map(|(_, line)| line)
+        .collect())
+}
+
+/// Splits the document at every heading of `options.level`. Returns the index, which is the
+/// document with every chapter replaced by a link to its file, and the chapters, whose headings
+/// are shifted so that the first one is an H1. Fragment links are rewritten to point into the
+/// file that now holds the anchor, and other relative links are rebased on the directory of
+/// their file.
+pub fn split(
+    lines: &[String],
+    options: &SplitOptions,
+) -> Result<(Vec<String>, Vec<Chapter>), ShiftError> {
+    let index_dir = parent_dir(&options.index_file);
+    let index_link = relative_link(&options.out_dir, &options.index_file);
+    let mut chapter_dir = relative_link(index_dir, &options.out_dir);
+    if !chapter_dir.is_empty() {
+        chapter_dir.push('/');
+    }
+    let headings = parse_headings(lines);
+    let slugs = heading_slugs(lines, options.slug_style);
+    let boundaries: Vec<usize> = (0..headings.len())
+        .filter(|&index| headings[index].level <= options.level)
+        .collect();
+
+    // The part of every heading: `None` for the index, or the chapter.
+    let mut owners: Vec<Option<usize>> = vec![None; headings.len()];
+    let mut chapters = Vec::new();
+    let mut index_lines = Vec::new();
+    let mut listing = false;
+    let mut items = Vec::new();
+    let first = boundaries
+        .first()
+        .map_or(lines.len(), |&b| headings[b].line);
+    push_block(&mut index_lines, &lines[..first], &mut listing);
+    for (position, &boundary) in boundaries.iter().enumerate() {
+        let heading = &headings[boundary];
+        let end = boundaries
+            .get(position + 1)
+            .map_or(lines.len(), |&next| headings[next].line);
+        let block = &lines[heading.line..trim_blank_end(lines, end)];
+        if heading.level < options.level {
+            push_block(&mut index_lines, block, &mut listing);
+            continue;
+        }
+        let chapter = chapters.len();
+        for (owner, candidate) in owners.iter_mut().zip(&headings) {
+            if (heading.line..end).contains(&candidate.line) {
+                *owner = Some(chapter);
+            }
+        }
+        let slug = SlugStyle::GitHub.slugify(&heading.text);
+        let name = if slug.is_empty() { "chapter" } else { &slug };
+        let file = format!("{:02}-{name}.md", chapter + 1);
+        let title = plain_text(&heading.text);
+        if !listing
+            && index_lines
+                .last()
+                .is_some_and(|line| !line.trim().is_empty())
+        {
+            index_lines.push(String::new());
+        }
+        items.push(index_lines.len());
+        index_lines.push(format!("- [{title}]({chapter_dir}{file})"));
+        listing = true;
+        chapters.push(Chapter {
+            file,
+            title,
+            lines: shift_levels(block, 1 - options.level as i8).map_err(|err| ShiftError {
+                line: err.line + heading.line,
+                ..err
+            })?,
+        });
+    }
+    while index_lines
+        .last()
+        .is_some_and(|line| line.trim().is_empty())
+    {
+        index_lines.pop();
+    }
+
+    // Maps the anchors of the document to the part and anchor they end up in.
+    let mut anchors: HashMap<&str, (Option<usize>, String)> = HashMap::new();
+    let mut new_slugs: Vec<std::vec::IntoIter<String>> = chapters
+        .iter()
+        .map(|chapter| heading_slugs(&chapter.lines, options.slug_style).into_iter())
+        .collect();
+    let mut index_slugs = heading_slugs(&index_lines, options.slug_style).into_iter();
+    for (slug, owner) in slugs.iter().zip(&owners) {
+        let new_slug = match owner {
+            Some(chapter) => new_slugs[*chapter].next(),
+            None => index_slugs.next(),
+        };
+        if let Some(new_slug) = new_slug {
+            anchors.entry(slug).or_insert((*owner, new_slug));
+        }
+    }
+    let link = |from: Option<usize>, target: &str, chapters: &[Chapter]| {
+        let Some(fragment) = target.strip_prefix('#') else {
+            let dir = from.map_or(index_dir, |_| &options.out_dir);
+            return rebase(target, &options.source_dir, dir);
+        };
+        let (to, slug) = anchors.get(fragment)?;
+        Some(match (from, *to) {
+            (from, to) if from == to => format!("#{slug}"),
+            (None, Some(to)) => format!("{chapter_dir}{}#{slug}", chapters[to].file),
+            (Some(_), Some(to)) => format!("{}#{slug}", chapters[to].file),
+            (_, None) => format!("{index_link}#{slug}"),
+        })
+    };
+    let snapshot = chapters.clone();
+    rewrite_links(&mut index_lines, |line, target| {
+        (!items.contains(&line)).then(|| link(None, target, &snapshot))?
+    });
+    for (index, chapter) in chapters.iter_mut().enumerate() {
+        rewrite_links(&mut chapter.lines, |_, target| {
+            link(Some(index), target, &snapshot)
+        });
+    }
+    Ok((index_lines, chapters))
+}
+
+/// Concatenates documents without their front matter, moving their headings down by
+/// `options.shift` levels under an optional H1 title. Fragment links, and relative links
+/// between the merged files, are rewritten to the anchors of the merged document, while other
+/// relative links are rebased on `options.out_dir`.
+pub fn merge(
+    documents: &[(PathBuf, Vec<String>)],
+    options: &MergeOptions,
+) -> Result<Vec<String>, ShiftError> {
+    let mut merged = Vec::new();
+    if let Some(title) = &options.title {
+        merged.push(format!("# {title}"));
+    }
+    let mut ranges = Vec::new();
+    for (file, lines) in documents {
+        let start = front_matter_end(lines).map_or(0, |end| end + 1);
+        let body =
+            shift_levels(&lines[start..], options.shift as i8).map_err(|err| ShiftError {
+                file: file.clone(),
+                line: err.line + start,
+                ..err
+            })?;
+        let first = body.iter().position(|line| !line.trim().is_empty());
+        let body = &body[first.unwrap_or(body.len())..trim_blank_end(&body, body.len())];
+        if !merged.is_empty() {
+            merged.push(String::new());
+        }
+        ranges.push(merged.len()..merged.len() + body.len());
+        merged.extend_from_slice(body);
+    }
+
+    let mut merged_slugs = heading_slugs(&merged, options.slug_style).into_iter();
+    if options.title.is_some() {
+        merged_slugs.next();
+    }
+    let anchors: Vec<HashMap<String, String>> = documents
+        .iter()
+        .map(|(_, lines)| {
+            let slugs = heading_slugs(lines, options.slug_style);
+            slugs.into_iter().zip(merged_slugs.by_ref()).collect()
+        })
+        .collect();
+    let first_anchors: Vec<Option<String>> = documents
+        .iter()
+        .zip(&anchors)
+        .map(|((_, lines), anchors)| {
+            let first = heading_slugs(lines, options.slug_style)
+                .into_iter()
+                .next()?;
+            anchors.get(&first).cloned()
+        })
+        .collect();
+    let files: Vec<Option<PathBuf>> = documents
+        .iter()
+        .map(|(path, _)| fs::canonicalize(path).ok())
+        .collect();
+    rewrite_links(&mut merged, |line, target| {
+        let document = ranges.iter().position(|range| range.contains(&line))?;
+        if is_external(target) {
+            return None;
+        }
+        let (path, fragment) = match target.split_once('#') {
+            Some((path, fragment)) => (path, Some(fragment)),
+            None => (target, None),
+        };
+        let target_document = if path.is_empty() {
+            document
+        } else {
+            let dir = documents[document].0.parent().unwrap_or(Path::new(""));
+            let file = fs::canonicalize(dir.join(path)).ok();
+            match files.iter().position(|c| file.is_some() && *c == file) {
+                Some(target_document) => target_document,
+                None => return rebase(target, dir, &options.out_dir),
+            }
+        };
+        let slug = match fragment {
+            Some(fragment) => anchors[target_document].get(fragment)?,
+            None => first_anchors[target_document].as_ref()?,
+        };
+        Some(format!("#{slug}"))
+    });
+    Ok(merged)
+}
+
+/// Returns the link from the directory `from` to `to`, which are resolved from the working
+/// directory, with `/` separators.
+pub fn relative_link(from: &Path, to: &Path) -> String {
+    let (from, to) = (normalize(from), normalize(to));
+    let from: Vec<Component> = from.components().collect();
+    let to: Vec<Component> = to.components().collect();
+    let common = from.iter().zip(&to).take_while(|(a, b)| a == b).count();
+    let mut parts: Vec<String> = vec!["..".to_owned(); from.len() - common];
+    parts.extend(
+        to[common..]
+            .iter()
+            .map(|part| part.as_os_str().to_string_lossy().into_owned()),
+    );
+    parts.join("/")
+}
+
+/// Rewrites a relative link written in the directory `from` for a file in the directory `to`.
+fn rebase(target: &str, from: &Path, to: &Path) -> Option<String> {
+    if is_external(target) || target.starts_with('#') || normalize(from) == normalize(to) {
+        return None;
+    }
+    let (path, suffix) = target
+        .find(['#', '?'])
+        .map_or((target, ""), |end| target.split_at(end));
+    Some(format!("{}{suffix}", relative_link(to, &from.join(path))))
+}
+
+fn parent_dir(file: &Path) -> &Path {
+    file.parent()
+        .filter(|dir| !dir.as_os_str().is_empty())
+        .unwrap_or(Path::new("."))
+}
+
+/// Makes the path absolute and resolves `.` and `..` without touching the file system.
+fn normalize(path: &Path) -> PathBuf {
+    let path = if path.as_os_str().is_empty() {
+        Path::new(".")
+    } else {
+        path
+    };
+    let path = std::path::absolute(path).unwrap_or_else(|_| path.to_path_buf());
+    let mut normalized = PathBuf::new();
+    for component in path.components() {
+        match component {
+            Component::CurDir => {}
+            Component::ParentDir => {
+                normalized.pop();
+            }
+            component => normalized.push(component),
+        }
+    }
+    normalized
+}
+
+fn heading_slugs(lines: &[String], slug_style: SlugStyle) -> Vec<String> {
+    let mut slugger = Slugger::new(slug_style);
+    parse_headings(lines)
+        .iter()
+        .map(|heading| heading.anchor(&mut slugger))
+        .collect()
+}
+
+fn same_title(heading: &Heading, title: &str) -> bool {
+    plain_text(&heading.text).trim().to_lowercase() == title.to_lowercase()
+}
+
+fn section_end(headings: &[Heading], index: usize, len: usize) -> usize {
+    headings[index + 1..]
+        .iter()
+        .find(|next| next.level <= headings[index].level)
+        .map_or(len, |next| next.line)
+}
+
+fn trim_blank_end(lines: &[String], mut end: usize) -> usize {
+    while end > 0 && lines[end - 1].trim().is_empty() {
+        end -= 1;
+    }
+    end
+}
+
+/// Appends a block of the index, separated by a blank line from a preceding list of chapters.
+fn push_block(index: &mut Vec<String>, block: &[String], listing: &mut bool) {
+    let block = &block[..trim_blank_end(block, block.len())];
+    if block.is_empty() {
+        return;
+    }
+    if *listing {
+        index.push(String::new());
+        *listing = false;
+    }
+    index.extend_from_slice(block);
+    index.push(String::new());
+}
+
+fn relevel_html(line: &str, from: u8, to: u8) -> String {
+    let mut bytes = line.as_bytes().to_vec();
+    let open = [format!("<h{from}"), format!("<H{from}")];
+    let close = [format!("</h{from}>"), format!("</H{from}>")];
+    if let Some(start) = open.iter().find_map(|tag| line.find(tag.as_str())) {
+        bytes[start + 2] = b'0' + to;
+    }
+    if let Some(start) = close.iter().find_map(|tag| line.rfind(tag.as_str())) {
+        bytes[start + 3] = b'0' + to;
+    }
+    String::from_utf8(bytes).unwrap_or_else(|_| line.to_owned())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn lines(content: &str) -> Vec<String> {
+        content.lines().map(str::to_owned).collect()
+    }
+
+    #[test]
+    fn test_find_section() {
+        let content = lines("# Doc\n## Install\n### Linux\napt\n\n### Mac\nbrew\n## Usage\nrun");
+        assert_eq!(Some(2..4), find_section(&content, "Install > Linux"));
+        assert_eq!(Some(2..4), find_section(&content, "doc > install>LINUX"));
+        assert_eq!(Some(2..4), find_section(&content, "Linux"));
+        assert_eq!(Some(7..9), find_section(&content, "Usage"));
+        assert_eq!(Some(1..7), find_section(&content, "Install"));
+        assert_eq!(None, find_section(&content, "Install > Usage"));
+        assert_eq!(None, find_section(&content, ""));
+    }
+
+    #[test]
+    fn test_shift_levels() {
+        let content = lines("Title\n=====\n# A #\n##### B\n<h2 id=\"c\">C</h2>\n```\n# code\n```");
+        let expected = lines("## Title\n## A\n###### B\n<h3 id=\"c\">C</h3>\n```\n# code\n```");
+        assert_eq!(Ok(expected), shift_levels(&content, 1));
+        assert_eq!(
+            Ok(lines("# A\n# B")),
+            shift_levels(&lines("## A\n## B"), -1)
+        );
+        let overflow = ShiftError {
+            file: PathBuf::new(),
+            line: 2,
+            level: 6,
+            shifted: 7,
+        };
+        assert_eq!(Err(overflow), shift_levels(&lines("##### A\n###### B"), 1));
+    }
+
+    #[test]
+    fn test_split() {
+        let content = lines(concat!(
+            "# Guide\nIntro, see [usage](#usage).\n\n",
+            "## Install\nRead [usage](#usage) and [intro](#guide).\n### Linux\n![](img/a.png)\n\n",
+            "## Usage\nBack to [linux](#linux).\n",
+        ));
+        let options = SplitOptions {
+            level: 2,
+            slug_style: SlugStyle::GitHub,
+            source_dir: PathBuf::from("/r"),
+            out_dir: PathBuf::from("/r/guide"),
+            index_file: PathBuf::from("/r/README.md"),
+        };
+        let (index, chapters) = split(&content, &options).unwrap();
+        let expected = lines(concat!(
+            "# Guide\nIntro, see [usage](guide/02-usage.md#usage).\n\n",
+            "- [Install](guide/01-install.md)\n- [Usage](guide/02-usage.md)",
+        ));
+        assert_eq!(expected, index);
+        assert_eq!(
+            vec!["01-install.md", "02-usage.md"],
+            chapters.iter().map(|c| c.file.as_str()).collect::<Vec<_>>()
+        );
+        let expected = lines(concat!(
+            "# Install\nRead [usage](02-usage.md#usage) and [intro](../README.md#guide).\n",
+            "## Linux\n![](../img/a.png)",
+        ));
+        assert_eq!(expected, chapters[0].lines);
+        assert_eq!(
+            lines("# Usage\nBack to [linux](01-install.md#linux)."),
+            chapters[1].lines
+        );
+    }
+
+    #[test]
+    fn test_merge() {
+        let dir = tempfile::tempdir().unwrap();
+        let a = dir.path().join("a.md");
+        let b = dir.path().join("b.md");
+        fs::write(&a, "").unwrap();
+        fs::write(&b, "").unwrap();
+        let documents = vec![
+            (
+                a,
+                lines("---\ntitle: A\n---\n# Usage\nSee [b](b.md#usage) and [b](./b.md)."),
+            ),
+            (
+                b,
+                lines("\n# Usage\n## Usage\nSee [top](#usage), [a](a.md#usage) and [c](c.md#c)."),
+            ),
+        ];
+        let options = MergeOptions {
+            title: Some("Book".to_owned()),
+            shift: 1,
+            slug_style: SlugStyle::GitHub,
+            out_dir: dir.path().join("book"),
+        };
+        let expected = lines(concat!(
+            "# Book\n\n## Usage\nSee [b](#usage-1) and [b](#usage-1).\n\n",
+            "## Usage\n### Usage\nSee [top](#usage-1), [a](#usage) and [c](../c.md#c).",
+        ));
+        assert_eq!(Ok(expected), merge(&documents, &options));
+    }
+
+    #[test]
+    fn test_relative_link() {
+        assert_eq!(
+            "docs/a.md",
+            relative_link(Path::new("/r"), Path::new("/r/docs/a.md"))
+        );
+        assert_eq!(
+            "../README.md",
+            relative_link(Path::new("/r/docs"), Path::new("/r/README.md"))
+        );
+        assert_eq!("", relative_link(Path::new("/r"), Path::new("/r/./x/..")));
+    }
+}
@@ -0,0 +1,213 @@
+use crate::i18n::{self, Message};
+use mdtoc_core::heading::{Heading, LevelRange};
+use mdtoc_core::slug::plain_text;
+use mdtoc_core::stats::{section_range, SectionStats};
+use mdtoc_core::text::display_width;
+use serde_json::{json, Value};
+use std::path::Path;
+use std::process::Command;
+
+const DAY: u64 = 24 * 60 * 60;
+
+/// A listed heading with the statistics of its section.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SectionRow {
+    pub level: u8,
+    pub title: String,
+    /// 1-based line number.
+    pub line: usize,
+    pub stats: SectionStats,
+    /// Seconds since the newest line of the section was committed, if the file is tracked.
+    pub age: Option<u64>,
+}
+
+/// Returns the commit time of every line of a file from `git blame`. Uncommitted lines have the
+/// current time.
+pub fn blame_times(file: &Path) -> Result<Vec<u64>, String> {
+    let dir = file
+        .parent()
+        .filter(|dir| !dir.as_os_str().is_empty())
+        .unwrap_or(Path::new("."));
+    let name = file
+        .file_name()
+        .ok_or_else(|| format!("`{}` is not a file", file.display()))?;
+    let output = Command::new("git")
+        .arg("-C")
+        .arg(dir)
+        .args(["blame", "--line-porcelain", "--"])
+        .arg(name)
+        .output()
+        .map_err(|err| format!("cannot run git: {err}"))?;
+    if !output.status.success() {
+        return Err(String::from_utf8_lossy(&output.stderr).trim().to_owned());
+    }
+    // Content lines start with a tab, so no line of the file is taken for a header.
+    Ok(String::from_utf8_lossy(&output.stdout)
+        .lines()
+        .filter_map(|line| line.strip_prefix("committer-time "))
+        .filter_map(|time| time.parse().ok())
+        .collect())
+}
+
+/// Pairs the listed headings with the statistics of their sections, aging each section by its
+/// newest line in `times`.
+pub fn section_rows(
+    headings: &[Heading],
+    stats: &[SectionStats],
+    levels: LevelRange,
+    times: Option<&[u64]>,
+    now: u64,
+    len: usize,
+) -> Vec<SectionRow> {
+    (0..headings.len())
+        .filter(|&index| headings[index].is_listed(levels))
+        .map(|index| {
+            let heading = &headings[index];
+            let range = section_range(headings, index, len);
+            let newest = times
+                .and_then(|times| times.get(range))
+                .and_then(|times| times.iter().max());
+            SectionRow {
+                level: heading.level,
+                title: plain_text(&heading.text),
+                line: heading.line + 1,
+                stats: stats[index],
+                age: newest.map(|time| now.saturating_sub(*time)),
+            }
+        })
+        .collect()
+}
+
+/// The age of a section in the largest unit that fits, worded by the i18n catalogs.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Age {
+    Today,
+    Days(u64),
+    Weeks(u64),
+    Months(u64),
+    Years(u64),
+}
+
+impl Age {
+    pub fn from_seconds(seconds: u64) -> Self {
+        match seconds / DAY {
+            0 => Age::Today,
+            days if days < 14 => Age::Days(days),
+            days if days < 60 => Age::Weeks(days / 7),
+            days if days < 730 => Age::Months(days / 30),
+            days => Age::Years(days / 365),
+        }
+    }
+}
+
+/// Renders the rows as a table, with the titles indented by level under the file name.
+pub fn render_text(file: &str, rows: &[SectionRow]) -> Vec<String> {
+    let min_level = rows.iter().map(|row| row.level).min().unwrap_or(1);
+    let cells: Vec<[String; 7]> = rows
+        .iter()
+        .map(|row| {
+            let indent = "  ".repeat(usize::from(row.level - min_level));
+            [
+                format!("{indent}{}", row.title),
+                row.stats.words.to_string(),
+                Message::ReadingTime(row.stats.reading_minutes()).to_string(),
+                row.stats.code_blocks.to_string(),
+                row.stats.images.to_string(),
+                row.stats.links.to_string(),
+                row.age.map_or_else(
+                    || "-".to_owned(),
+                    |seconds| Message::Age(Age::from_seconds(seconds)).to_string(),
+                ),
+            ]
+        })
+        .collect();
+    let columns = i18n::stats_columns(i18n::lang());
+    let mut widths = columns.map(display_width);
+    for row in &cells {
+        for (width, cell) in widths.iter_mut().zip(row) {
+            *width = (*width).max(display_width(cell));
+        }
+    }
+    let header = columns.map(str::to_owned);
+    let mut lines = vec![file.to_owned()];
+    for row in std::iter::once(&header).chain(&cells) {
+        let line: Vec<String> = row
+            .iter()
+            .zip(widths)
+            .enumerate()
+            .map(|(column, (cell, width))| {
+                let pad = " ".repeat(width - display_width(cell));
+                match column {
+                    0 | 6 => format!("{cell}{pad}"),
+                    _ => format!("{pad}{cell}"),
+                }
+            })
+            .collect();
+        lines.push(line.join("  ").trim_end().to_owned());
+    }
+    lines
+}
+
+pub fn to_json(file: &str, rows: &[SectionRow]) -> Value {
+    let sections: Vec<Value> = rows
+        .iter()
+        .map(|row| {
+            json!({
+                "level": row.level,
+                "title": row.title,
+                "line": row.line,
+                "words": row.stats.words,
+                "reading_minutes": row.stats.reading_minutes(),
+                "code_blocks": row.stats.code_blocks,
+                "images": row.stats.images,
+                "links": row.stats.links,
+                "age_days": row.age.map(|age| age / DAY),
+            })
+        })
+        .collect();
+    json!({ "file": file, "sections": sections })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use mdtoc_core::heading::parse_headings;
+    use mdtoc_core::stats::section_stats;
+
+    #[test]
+    fn test_section_rows() {
+        let lines: Vec<String> = "# Guide\nIntro.\n## 安装\n运行 it\n## Usage\n"
+            .lines()
+            .map(str::to_owned)
+            .collect();
+        let headings = parse_headings(&lines);
+        let stats = section_stats(&lines, &headings);
+        let times = [0, 0, 10 * DAY, 0, 100 * DAY];
+        let now = 100 * DAY;
+        let rows = section_rows(
+            &headings,
+            &stats,
+            LevelRange::default(),
+            Some(&times),
+            now,
+            5,
+        );
+        let ages: Vec<_> = rows
+            .iter()
+            .map(|row| row.age.map(Age::from_seconds))
+            .collect();
+        let expected = vec![Some(Age::Today), Some(Age::Months(3)), Some(Age::Today)];
+        assert_eq!(expected, ages);
+
+        let expected = vec![
+            "README.md",
+            "Section  Words   Read  Code  Images  Links  Age",
+            "Guide        4  1 min     0       0      0  today",
+            "  安装       3  1 min     0       0      0  3 months",
+            "  Usage      0  0 min     0       0      0  today",
+        ];
+        assert_eq!(expected, render_text("README.md", &rows));
+        assert_eq!(Age::Weeks(2), Age::from_seconds(20 * DAY));
+        assert_eq!(Age::Years(2), Age::from_seconds(800 * DAY));
+    }
+}
@@ -0,0 +1,114 @@
+use crate::batch;
+use std::collections::{BTreeSet, HashMap};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant, SystemTime};
+
+/// How often the files are checked for changes.
+pub const POLL_INTERVAL: Duration = Duration::from_millis(250);
+/// How long the files must be left alone after a change before they are processed, so that an
+/// editor saving several times in a row triggers a single run.
+pub const DEBOUNCE: Duration = Duration::from_millis(500);
+
+/// The modification time and length of a file, which tell whether it changed since last seen.
+type Stamp = (SystemTime, u64);
+
+/// Polls the files under some paths for changes. Files are expanded on every poll, so that files
+/// added to a watched directory are picked up.
+pub struct Watcher {
+    paths: Vec<String>,
+    extensions: &'static [&'static str],
+    debounce: Duration,
+    stamps: HashMap<PathBuf, Stamp>,
+    /// The changed files that wait for the changes to settle.
+    pending: BTreeSet<PathBuf>,
+    last_change: Option<Instant>,
+}
+
+impl Watcher {
+    /// Creates a watcher that sees every file as changed on the first poll.
+    pub fn new(
+        paths: Vec<String>,
+        extensions: &'static [&'static str],
+        debounce: Duration,
+    ) -> Self {
+        Watcher {
+            paths,
+            extensions,
+            debounce,
+            stamps: HashMap::new(),
+            pending: BTreeSet::new(),
+            last_change: None,
+        }
+    }
+
+    /// Returns the files changed since they were last seen, once none has changed for the
+    /// debounce time.
+    pub fn poll(&mut self) -> Result<Vec<PathBuf>, String> {
+        let files = batch::collect_files(&self.paths, self.extensions)?;
+        let mut stamps = HashMap::new();
+        for file in files {
+            let Some(stamp) = fs::metadata(&file).ok().as_ref().and_then(stamp) else {
+                continue;
+            };
+            if self.stamps.get(&file) != Some(&stamp) {
+                self.pending.insert(file.clone());
+                self.last_change = Some(Instant::now());
+            }
+            stamps.insert(file, stamp);
+        }
+        self.pending.retain(|file| stamps.contains_key(file));
+        self.stamps = stamps;
+        match self.last_change {
+            Some(last_change) if last_change.elapsed() >= self.debounce => {
+                self.last_change = None;
+                Ok(std::mem::take(&mut self.pending).into_iter().collect())
+            }
+            _ => Ok(Vec::new()),
+        }
+    }
+
+    /// Takes the file as written by the watch as seen, so that the write is not taken for a
+    /// change. The metadata is that of the write, so that a save landing after it still counts.
+    pub fn written(&mut self, file: &Path, metadata: &fs::Metadata) {
+        if let Some(stamp) = stamp(metadata) {
+            self.stamps.insert(file.to_path_buf(), stamp);
+        }
+    }
+}
+
+fn stamp(metadata: &fs::Metadata) -> Option<Stamp> {
+    Some((metadata.modified().ok()?, metadata.len()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_poll() {
+        let dir = tempfile::tempdir().unwrap();
+        let file = dir.path().join("a.md");
+        fs::write(&file, "# A\n").unwrap();
+        let paths = vec![dir.path().to_string_lossy().into_owned()];
+        let mut watcher = Watcher::new(paths.clone(), &["md"], Duration::ZERO);
+        assert_eq!(vec![file.clone()], watcher.poll().unwrap());
+        assert!(watcher.poll().unwrap().is_empty());
+
+        fs::write(&file, "# A\n## B\n").unwrap();
+        assert_eq!(vec![file.clone()], watcher.poll().unwrap());
+        fs::write(&file, "# A\n\n## B\n").unwrap();
+        watcher.written(&file, &fs::metadata(&file).unwrap());
+        assert!(watcher.poll().unwrap().is_empty());
+        let written = fs::metadata(&file).unwrap();
+        fs::write(&file, "# A\n\n## B\n\n## C\n").unwrap();
+        watcher.written(&file, &written);
+        assert_eq!(vec![file.clone()], watcher.poll().unwrap());
+
+        let mut watcher = Watcher::new(paths, &["md"], Duration::from_secs(3600));
+        assert!(watcher.poll().unwrap().is_empty());
+        fs::remove_file(&file).unwrap();
+        assert!(watcher.poll().unwrap().is_empty());
+        assert!(watcher.pending.is_empty());
+    }
+}
@@ -0,0 +1,189 @@
+use crate::front_matter::{self, FileOptions};
+use crate::heading::{Heading, LevelRange};
+use crate::ids::{self, IdStyle};
+use crate::insert;
+use crate::markup::notebook::Notebook;
+use crate::markup::Markup;
+use crate::order::{self, OrderStyle};
+use crate::slug::SlugStyle;
+use crate::stats::{self, SectionStats};
+use crate::text::{Text, BOM};
+use crate::toc::{self, TocEntry, TocOptions};
+use crate::Error;
+
+/// A document split into lines, which are rendered back in the markup, line ending and trailing
+/// newline it was read with. A notebook is edited as the markdown document of its markdown
+/// cells.
+#[derive(Debug)]
+pub struct Document {
+    text: Text,
+    markup: Markup,
+    notebook: Option<Notebook>,
+}
+
+impl Document {
+    /// Parses a markdown document.
+    pub fn parse(content: &str) -> Document {
+        Document {
+            text: Text::parse(content),
+            markup: Markup::Markdown,
+            notebook: None,
+        }
+    }
+
+    /// Parses a document in `markup`, which fails only for a notebook that is not valid.
+    pub fn parse_markup(content: &str, markup: Markup) -> Result<Document, Error> {
+        if markup != Markup::Notebook {
+            return Ok(Document {
+                markup,
+                ..Document::parse(content)
+            });
+        }
+        let bom = content.starts_with(BOM);
+        let notebook = Notebook::parse(content.trim_start_matches(BOM))?;
+        Ok(Document {
+            text: Text {
+                lines: notebook.markdown(),
+                eol: "\n",
+                endings: Vec::new(),
+                trailing_newline: true,
+                bom,
+            },
+            markup,
+            notebook: Some(notebook),
+        })
+    }
+
+    pub fn markup(&self) -> Markup {
+        self.markup
+    }
+
+    pub fn lines(&self) -> &[String] {
+        &self.text.lines
+    }
+
+    pub fn headings(&self) -> Vec<Heading> {
+        self.markup.front_end().parse_headings(&self.text.lines)
+    }
+
+    /// The TOC options of the front matter.
+    pub fn file_options(&self) -> FileOptions {
+        front_matter::file_options(&self.text.lines)
+    }
+
+    /// Returns the outline tree of the listed headings, with the section statistics as notes if
+    /// `options.stats` is set.
+    pub fn outline(&self, options: &TocOptions) -> Vec<TocEntry> {
+        let headings = self.headings();
+        let mut outline = toc::build_outline(&headings, options.slug_style, options.levels);
+        if options.stats {
+            let stats = self.section_stats();
+            annotate(&mut outline, &headings, &stats);
+        }
+        outline
+    }
+
+    /// Returns the statistics of the section of every heading, leaving out the inserted TOC.
+    pub fn section_stats(&self) -> Vec<SectionStats> {
+        let mut lines = self.text.lines.clone();
+        if let Some((start, stop)) = insert::find_markers(&lines, self.markup.front_end()) {
+            lines[start..=stop].fill(String::new());
+        }
+        stats::section_stats(&lines, &self.headings())
+    }
+
+    /// Renders the TOC in the syntax of the markup, or in `options.format`, with the line ending
+    /// of the document.
+    pub fn toc(&self, options: &TocOptions) -> String {
+        let toc = self.render_toc(options);
+        let text = Text {
+            lines: Vec::new(),
+            endings: Vec::new(),
+            bom: false,
+            ..self.text
+        };
+        text.render(&toc)
+    }
+
+    /// Numbers the titles of the headings within `levels`, replacing any previous numbers.
+    pub fn apply_numbering(&mut self, style: OrderStyle, levels: LevelRange) {
+        let headings = self.headings();
+        order::number_headings(&mut self.text.lines, &headings, style, levels);
+    }
+
+    /// Writes the current anchor onto every listed heading without an explicit ID. Explicit IDs
+    /// are markdown syntax, so documents in other markups are left as they are.
+    pub fn add_ids(&mut self, style: IdStyle, slug_style: SlugStyle, levels: LevelRange) {
+        if matches!(self.markup, Markup::Markdown | Markup::Notebook) {
+            self.text.lines = ids::add_ids(&self.text.lines, style, slug_style, levels);
+        }
+    }
+
+    /// Inserts the TOC between the TOC markers, or after the title if there are none.
+    pub fn insert_toc(&mut self, options: &TocOptions) {
+        let toc = self.render_toc(options);
+        let lines = std::mem::take(&mut self.text.lines);
+        self.text.lines = insert::insert_toc(lines, &toc, self.markup.front_end());
+    }
+
+    fn render_toc(&self, options: &TocOptions) -> Vec<String> {
+        self.markup
+            .front_end()
+            .render_toc(&self.outline(options), options)
+    }
+
+    /// Renders the document back in its markup.
+    pub fn render(&self) -> String {
+        match &self.notebook {
+            Some(notebook) if self.text.bom => {
+                format!("{BOM}{}", notebook.render(&self.text.lines))
+            }
+            Some(notebook) => notebook.render(&self.text.lines),
+            None => self.text.render(&self.text.lines),
+        }
+    }
+}
+
+fn annotate(entries: &mut [TocEntry], headings: &[Heading], stats: &[SectionStats]) {
+    for entry in entries {
+        if let Some(index) = headings
+            .iter()
+            .position(|heading| heading.line == entry.line)
+        {
+            entry.note = Some(stats[index].summary());
+        }
+        annotate(&mut entry.children, headings, stats);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_document() {
+        let mut document = Document::parse("\u{feff}# A\r\n## B\r\n");
+        let options = TocOptions::default();
+        assert_eq!("- [A](#a)\r\n  - [B](#b)\r\n", document.toc(&options));
+
+        document.apply_numbering(OrderStyle::Decimal, LevelRange::default());
+        document.insert_toc(&options);
+        let expected = "\u{feff}# 1 A\r\n\r\n<!-- toc -->\r\n- [1 A](#1-a)\r\n  - [1.1 B](#11-b)\r\n<!-- tocstop -->\r\n\r\n## 1.1 B\r\n";
+        assert_eq!(expected, document.render());
+
+        let document =
+            Document::parse_markup("Title\n=====\n\nUsage\n-----\n", Markup::Rst).unwrap();
+        assert_eq!("- `Usage`_\n", document.toc(&options));
+        assert!(Document::parse_markup("{}", Markup::Notebook).is_err());
+
+        let mut document = Document::parse("# A\n## B\none two\n## C\n");
+        let options = TocOptions {
+            stats: true,
+            ..Default::default()
+        };
+        document.insert_toc(&options);
+        document.insert_toc(&options);
+        let expected = "- [A](#a) (2 words, 1 min)\n  - [B](#b) (2 words, 1 min)\n  - [C](#c) (0 words, 0 min)\n";
+        assert_eq!(expected, document.toc(&options));
+    }
+}
@@ -0,0 +1,131 @@
+use crate::Error;
+use std::fs;
+use std::io;
+use std::path::Path;
+use std::str::FromStr;
+
+/// A text encoding from the WHATWG Encoding Standard, such as UTF-8, UTF-16LE or GBK.
+///
+/// Decoding keeps a BOM as a leading U+FEFF, so that encoding the text writes it back.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Encoding(&'static encoding_rs::Encoding);
+
+impl Default for Encoding {
+    fn default() -> Self {
+        Encoding(encoding_rs::UTF_8)
+    }
+}
+
+impl FromStr for Encoding {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match encoding_rs::Encoding::for_label(s.trim().as_bytes()) {
+            Some(encoding) => Ok(Encoding(encoding)),
+            None => Err(Error::UnknownValue {
+                kind: "encoding",
+                value: s.to_owned(),
+                expected: "utf-8, utf-16le, utf-16be, gbk, gb18030, big5, shift_jis",
+            }),
+        }
+    }
+}
+
+impl Encoding {
+    pub fn name(self) -> &'static str {
+        self.0.name()
+    }
+
+    /// Detects UTF-8 and UTF-16 by their BOM, or UTF-16 without one by its zero bytes, and
+    /// defaults to UTF-8.
+    pub fn detect(bytes: &[u8]) -> Encoding {
+        if let Some((encoding, _)) = encoding_rs::Encoding::for_bom(bytes) {
+            return Encoding(encoding);
+        }
+        let zeros = |parity: usize| {
+            bytes
+                .iter()
+                .skip(parity)
+                .step_by(2)
+                .filter(|&&byte| byte == 0)
+                .count()
+        };
+        // ASCII characters, which markdown is full of, have a zero high byte in UTF-16.
+        let half = bytes.len() / 4;
+        match (zeros(0), zeros(1)) {
+            (even, odd) if odd > half && even == 0 => Encoding(encoding_rs::UTF_16LE),
+            (even, odd) if even > half && odd == 0 => Encoding(encoding_rs::UTF_16BE),
+            _ => Encoding::default(),
+        }
+    }
+
+    /// Decodes the bytes, failing on malformed input rather than replacing it.
+    pub fn decode(self, bytes: &[u8]) -> Result<String, Error> {
+        self.0
+            .decode_without_bom_handling_and_without_replacement(bytes)
+            .map(|content| content.into_owned())
+            .ok_or(Error::Malformed(self.name()))
+    }
+
+    /// Encodes the text, failing on characters the encoding cannot represent.
+    pub fn encode(self, content: &str) -> Result<Vec<u8>, Error> {
+        if self.0 == encoding_rs::UTF_16LE {
+            return Ok(content.encode_utf16().flat_map(u16::to_le_bytes).collect());
+        }
+        if self.0 == encoding_rs::UTF_16BE {
+            return Ok(content.encode_utf16().flat_map(u16::to_be_bytes).collect());
+        }
+        let (bytes, _, unmappable) = self.0.encode(content);
+        match unmappable {
+            true => Err(Error::Unmappable(self.name())),
+            false => Ok(bytes.into_owned()),
+        }
+    }
+}
+
+/// Reads a file in `encoding`, or else in the one detected from its content. Malformed input is
+/// an `InvalidData` error, like `fs::read_to_string` returns for UTF-8.
+pub fn read_file(file: &Path, encoding: Option<Encoding>) -> io::Result<(String, Encoding)> {
+    let bytes = fs::read(file)?;
+    let encoding = encoding.unwrap_or_else(|| Encoding::detect(&bytes));
+    let content = encoding
+        .decode(&bytes)
+        .map_err(|err| io::Error::new(io::ErrorKind::InvalidData, err))?;
+    Ok((content, encoding))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_round_trip() {
+        let utf16le: Vec<u8> = "\u{feff}# 目录\r\n"
+            .encode_utf16()
+            .flat_map(u16::to_le_bytes)
+            .collect();
+        let gbk = b"# \xd6\xd0\xce\xc4\n".to_vec();
+        assert_eq!("UTF-8", Encoding::detect(b"\xef\xbb\xbf# A\n").name());
+        assert_eq!("UTF-16LE", Encoding::detect(&utf16le).name());
+        assert_eq!("UTF-16LE", Encoding::detect(&utf16le[2..]).name());
+        assert_eq!("UTF-8", Encoding::detect(&gbk).name());
+        let cases = [
+            (b"\xef\xbb\xbf# A\n".to_vec(), Encoding::default()),
+            (utf16le.clone(), Encoding::detect(&utf16le)),
+            (gbk.clone(), "gbk".parse().unwrap()),
+        ];
+        for (bytes, encoding) in cases {
+            let content = encoding.decode(&bytes).unwrap();
+            assert_eq!(bytes, encoding.encode(&content).unwrap());
+        }
+        assert_eq!(
+            "# 中文\n",
+            Encoding::from_str("gbk").unwrap().decode(&gbk).unwrap()
+        );
+        assert_eq!(
+            Err(Error::Malformed("UTF-8")),
+            Encoding::default().decode(&gbk)
+        );
+        assert!("latin-9000".parse::<Encoding>().is_err());
+    }
+}
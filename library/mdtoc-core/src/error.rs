@@ -0,0 +1,37 @@
+use std::error;
+use std::fmt::{Display, Formatter};
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Error {
+    /// A value that is none of the choices of an option, such as an unknown slug style.
+    UnknownValue {
+        kind: &'static str,
+        value: String,
+        expected: &'static str,
+    },
+    /// A notebook that is not JSON, or has no `cells` array.
+    InvalidNotebook(String),
+    /// Input that is not valid in the encoding.
+    Malformed(&'static str),
+    /// Text with characters the encoding cannot represent.
+    Unmappable(&'static str),
+}
+
+impl Display for Error {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Error::UnknownValue {
+                kind,
+                value,
+                expected,
+            } => write!(f, "unknown {kind} `{value}`, expect one of {expected}"),
+            Error::InvalidNotebook(msg) => write!(f, "invalid notebook: {msg}"),
+            Error::Malformed(encoding) => write!(f, "the input is not valid {encoding}"),
+            Error::Unmappable(encoding) => {
+                write!(f, "the result has characters {encoding} cannot represent")
+            }
+        }
+    }
+}
+
+impl error::Error for Error {}
@@ -0,0 +1,251 @@
+use crate::slug::plain_text;
+use crate::toc::{TocEntry, TocOptions};
+use crate::Error;
+use serde_json::{json, Value};
+use std::str::FromStr;
+
+/// The title of the formats that require one, when none is set.
+pub const TOC_TITLE: &str = "Table of Contents";
+
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+pub enum TocFormat {
+    #[default]
+    Markdown,
+    Html,
+    Json,
+    Opml,
+    Org,
+}
+
+impl FromStr for TocFormat {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "markdown" | "md" => Ok(TocFormat::Markdown),
+            "html" => Ok(TocFormat::Html),
+            "json" => Ok(TocFormat::Json),
+            "opml" => Ok(TocFormat::Opml),
+            "org" => Ok(TocFormat::Org),
+            _ => Err(Error::UnknownValue {
+                kind: "format",
+                value: s.to_owned(),
+                expected: "markdown, html, json, opml, org",
+            }),
+        }
+    }
+}
+
+impl TocFormat {
+    /// Whether the output can be embedded into a markdown document with `--insert`.
+    pub fn is_embeddable(&self) -> bool {
+        matches!(self, TocFormat::Markdown | TocFormat::Html)
+    }
+}
+
+pub fn render(outline: &[TocEntry], options: &TocOptions) -> Vec<String> {
+    let mut lines = Vec::new();
+    let title = options.title.as_deref().unwrap_or(TOC_TITLE);
+    match options.format {
+        TocFormat::Markdown => {
+            if let Some(title) = &options.title {
+                lines.push(format!("**{title}**"));
+                lines.push(String::new());
+            }
+            markdown(&mut lines, outline, 0)
+        }
+        TocFormat::Html => {
+            if options.collapsible {
+                lines.push("<details>".to_owned());
+                lines.push(format!("<summary>{}</summary>", escape(title)));
+                lines.push(String::new());
+            }
+            lines.push("<nav>".to_owned());
+            html(&mut lines, outline, 0);
+            lines.push("</nav>".to_owned());
+            if options.collapsible {
+                lines.push(String::new());
+                lines.push("</details>".to_owned());
+            }
+        }
+        TocFormat::Json => {
+            let outline = Value::Array(outline.iter().map(to_json).collect());
+            let pretty = serde_json::to_string_pretty(&outline).unwrap_or_default();
+            lines.extend(pretty.lines().map(str::to_owned));
+        }
+        TocFormat::Opml => {
+            lines.push(r#"<?xml version="1.0" encoding="UTF-8"?>"#.to_owned());
+            lines.push(r#"<opml version="2.0">"#.to_owned());
+            lines.push("  <head>".to_owned());
+            lines.push(format!("    <title>{}</title>", escape(title)));
+            lines.push("  </head>".to_owned());
+            lines.push("  <body>".to_owned());
+            opml(&mut lines, outline, 2);
+            lines.push("  </body>".to_owned());
+            lines.push("</opml>".to_owned());
+        }
+        TocFormat::Org => org(&mut lines, outline, 0),
+    }
+    lines
+}
+
+fn markdown(lines: &mut Vec<String>, entries: &[TocEntry], depth: usize) {
+    for entry in entries {
+        lines.push(format!(
+            "{}- [{}](#{}){}",
+            indent(depth),
+            entry.text,
+            entry.slug,
+            entry.note_suffix()
+        ));
+        markdown(lines, &entry.children, depth + 1);
+    }
+}
+
+fn html(lines: &mut Vec<String>, entries: &[TocEntry], depth: usize) {
+    if entries.is_empty() {
+        return;
+    }
+    lines.push(format!("{}<ul>", indent(depth * 2)));
+    for entry in entries {
+        let link = format!(
+            r##"<a href="#{}">{}</a>{}"##,
+            escape(&entry.slug),
+            escape(&plain_text(&entry.text)),
+            escape(&entry.note_suffix())
+        );
+        if entry.children.is_empty() {
+            lines.push(format!("{}<li>{link}</li>", indent(depth * 2 + 1)));
+        } else {
+            lines.push(format!("{}<li>{link}", indent(depth * 2 + 1)));
+            html(lines, &entry.children, depth + 1);
+            lines.push(format!("{}</li>", indent(depth * 2 + 1)));
+        }
+    }
+    lines.push(format!("{}</ul>", indent(depth * 2)));
+}
+
+fn to_json(entry: &TocEntry) -> Value {
+    let mut value = json!({
+        "level": entry.level,
+        "text": plain_text(&entry.text),
+        "slug": entry.slug,
+        "line": entry.line + 1,
+        "children": entry.children.iter().map(to_json).collect::<Vec<Value>>(),
+    });
+    if let Some(note) = &entry.note {
+        value["note"] = json!(note);
+    }
+    value
+}
+
+fn opml(lines: &mut Vec<String>, entries: &[TocEntry], depth: usize) {
+    for entry in entries {
+        let outline = format!(
+            r##"{}<outline text="{}" url="#{}""##,
+            indent(depth),
+            escape(&plain_text(&entry.text)),
+            escape(&entry.slug)
+        );
+        if entry.children.is_empty() {
+            lines.push(format!("{outline}/>"));
+        } else {
+            lines.push(format!("{outline}>"));
+            opml(lines, &entry.children, depth + 1);
+            lines.push(format!("{}</outline>", indent(depth)));
+        }
+    }
+}
+
+fn org(lines: &mut Vec<String>, entries: &[TocEntry], depth: usize) {
+    for entry in entries {
+        lines.push(format!(
+            "{}- [[#{}][{}]]{}",
+            indent(depth),
+            entry.slug,
+            plain_text(&entry.text),
+            entry.note_suffix()
+        ));
+        org(lines, &entry.children, depth + 1);
+    }
+}
+
+fn escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+fn indent(depth: usize) -> String {
+    "  ".repeat(depth)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::heading::parse_headings;
+    use crate::toc::build_outline;
+
+    fn render_format(format: TocFormat, collapsible: bool) -> Vec<String> {
+        let content: Vec<String> = "# A & B\n## C".lines().map(str::to_owned).collect();
+        let options = TocOptions {
+            format,
+            collapsible,
+            ..Default::default()
+        };
+        let outline = build_outline(
+            &parse_headings(&content),
+            options.slug_style,
+            options.levels,
+        );
+        render(&outline, &options)
+    }
+
+    #[test]
+    fn test_render_html() {
+        let expected = vec![
+            "<details>",
+            "<summary>Table of Contents</summary>",
+            "",
+            "<nav>",
+            "<ul>",
+            r##"  <li><a href="#a--b">A &amp; B</a>"##,
+            "    <ul>",
+            r##"      <li><a href="#c">C</a></li>"##,
+            "    </ul>",
+            "  </li>",
+            "</ul>",
+            "</nav>",
+            "",
+            "</details>",
+        ];
+        assert_eq!(expected, render_format(TocFormat::Html, true));
+    }
+
+    #[test]
+    fn test_render_json() {
+        let json: Value =
+            serde_json::from_str(&render_format(TocFormat::Json, false).join("\n")).unwrap();
+        let expected = json!([{
+            "level": 1, "text": "A & B", "slug": "a--b", "line": 1,
+            "children": [{ "level": 2, "text": "C", "slug": "c", "line": 2, "children": [] }],
+        }]);
+        assert_eq!(expected, json);
+    }
+
+    #[test]
+    fn test_render_opml_and_org() {
+        let opml = render_format(TocFormat::Opml, false);
+        assert_eq!(
+            vec![
+                r##"    <outline text="A &amp; B" url="#a--b">"##,
+                r##"      <outline text="C" url="#c"/>"##,
+                "    </outline>",
+            ],
+            opml[6..9]
+        );
+        let expected = vec!["- [[#a--b][A & B]]", "  - [[#c][C]]"];
+        assert_eq!(expected, render_format(TocFormat::Org, false));
+    }
+}
@@ -0,0 +1,111 @@
+use crate::heading::{front_matter_end, LevelRange};
+
+/// Per-file TOC options read from the `toc` key of the front matter, e.g.
+///
+/// ```yaml
+/// toc:
+///   depth: 3
+///   title: Contents
+/// ```
+///
+/// or a `[toc]` table in a TOML front matter. Supported keys are `depth`, `min-level`,
+/// `max-level` and `title`.
+#[derive(Debug, Default, PartialEq)]
+pub struct FileOptions {
+    pub min_level: Option<u8>,
+    pub max_level: Option<u8>,
+    pub depth: Option<u8>,
+    pub title: Option<String>,
+}
+
+impl FileOptions {
+    pub fn levels(&self, levels: LevelRange) -> LevelRange {
+        let min = self.min_level.unwrap_or(levels.min);
+        let max = match (self.max_level, self.depth) {
+            (Some(max), _) => max,
+            (None, Some(depth)) => (min + depth.max(1) - 1).min(6),
+            (None, None) => levels.max,
+        };
+        LevelRange { min, max }
+    }
+}
+
+pub fn file_options(lines: &[String]) -> FileOptions {
+    let mut options = FileOptions::default();
+    let end = match front_matter_end(lines) {
+        Some(end) => end,
+        None => return options,
+    };
+    let toml = lines[0].trim_end() == "+++";
+    let mut in_toc = false;
+    for line in &lines[1..end] {
+        let trimmed = line.trim();
+        if toml {
+            if trimmed.starts_with('[') {
+                in_toc = trimmed == "[toc]";
+                continue;
+            }
+        } else if !line.starts_with([' ', '\t']) {
+            in_toc = trimmed == "toc:";
+            continue;
+        }
+        if !in_toc {
+            continue;
+        }
+        let separator = if toml { '=' } else { ':' };
+        let Some((key, value)) = trimmed.split_once(separator) else {
+            continue;
+        };
+        let value = value.trim().trim_matches(['"', '\'']);
+        match key.trim().replace('_', "-").as_str() {
+            "depth" => options.depth = parse_level(value),
+            "min-level" => options.min_level = parse_level(value),
+            "max-level" => options.max_level = parse_level(value),
+            "title" => options.title = Some(value.to_owned()),
+            _ => {}
+        }
+    }
+    options
+}
+
+fn parse_level(value: &str) -> Option<u8> {
+    value.parse().ok().filter(|level| (1..=6).contains(level))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn lines(content: &str) -> Vec<String> {
+        content.lines().map(str::to_owned).collect()
+    }
+
+    #[test]
+    fn test_yaml_file_options() {
+        let content = lines("---\ntitle: Doc\ntoc:\n  min-level: 2\n  depth: 2\n  title: \"目录\"\nauthor: me\n---\n# Doc");
+        let options = file_options(&content);
+        let expected = FileOptions {
+            min_level: Some(2),
+            max_level: None,
+            depth: Some(2),
+            title: Some("目录".to_owned()),
+        };
+        assert_eq!(expected, options);
+        assert_eq!(
+            LevelRange { min: 2, max: 3 },
+            options.levels(LevelRange::default())
+        );
+    }
+
+    #[test]
+    fn test_toml_file_options() {
+        let content = lines("+++\ntitle = \"Doc\"\n[toc]\nmax_level = 3\n[extra]\ndepth = 1\n+++");
+        let options = file_options(&content);
+        assert_eq!(Some(3), options.max_level);
+        assert_eq!(None, options.depth);
+        assert_eq!(
+            LevelRange { min: 1, max: 3 },
+            options.levels(LevelRange::default())
+        );
+    }
+}
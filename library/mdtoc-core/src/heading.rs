@@ -0,0 +1,532 @@
+use crate::markup::{asciidoc, org, rst};
+use crate::slug::Slugger;
+use regex::Regex;
+use std::sync::OnceLock;
+
+const IGNORE_START: &str = "<!-- toc:ignore-start -->";
+const IGNORE_END: &str = "<!-- toc:ignore-end -->";
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum HeadingKind {
+    /// `# Title`
+    Atx,
+    /// `Title` underlined by `===` or `---` on the line `underline`.
+    Setext { underline: usize },
+    /// `<h2>Title</h2>` on a single line.
+    Html,
+    /// `== Title` in AsciiDoc.
+    AsciiDoc,
+    /// A reStructuredText title underlined on the line `underline`, and with `overline` on the
+    /// line before.
+    Rst { underline: usize, overline: bool },
+    /// `** Title :tag:` in Org.
+    Org,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Heading {
+    pub level: u8,
+    pub text: String,
+    pub line: usize,
+    pub kind: HeadingKind,
+    /// Marked by `<!-- omit in toc -->` or inside a `<!-- toc:ignore-start -->` section.
+    pub excluded: bool,
+    /// The explicit ID given by a trailing `{#id}`, the `id` of an HTML heading, or an
+    /// `<a id="id"></a>` on the line before.
+    pub id: Option<String>,
+}
+
+/// The heading levels taken into the TOC and the title order.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct LevelRange {
+    pub min: u8,
+    pub max: u8,
+}
+
+impl Default for LevelRange {
+    fn default() -> Self {
+        LevelRange { min: 1, max: 6 }
+    }
+}
+
+impl Heading {
+    pub fn is_listed(&self, levels: LevelRange) -> bool {
+        !self.excluded && (levels.min..=levels.max).contains(&self.level)
+    }
+
+    /// Returns the explicit ID, or the slug of the text. The slug is counted either way, like
+    /// renderers that generate it next to the explicit ID.
+    pub fn anchor(&self, slugger: &mut Slugger) -> String {
+        let slug = slugger.slug(&self.text);
+        self.id.clone().unwrap_or(slug)
+    }
+
+    /// Returns the last line taken by the heading.
+    pub fn end_line(&self) -> usize {
+        match self.kind {
+            HeadingKind::Setext { underline } | HeadingKind::Rst { underline, .. } => underline,
+            HeadingKind::Atx | HeadingKind::Html | HeadingKind::AsciiDoc | HeadingKind::Org => {
+                self.line
+            }
+        }
+    }
+
+    /// Returns the title written on the first line of the heading.
+    pub fn title<'a>(&self, lines: &'a [String]) -> &'a str {
+        let line = &lines[self.line];
+        match self.kind {
+            HeadingKind::Atx => parse_atx(line).map_or("", |(_, text)| text),
+            HeadingKind::Setext { .. } | HeadingKind::Rst { .. } => line.trim(),
+            HeadingKind::Html => {
+                html_heading(line).map_or("", |(_, open, close)| &line[open..close])
+            }
+            HeadingKind::AsciiDoc => asciidoc::parse_title(line).map_or("", |(_, text)| text),
+            HeadingKind::Org => org::parse_title(line).map_or("", |(_, text, _)| text),
+        }
+    }
+
+    /// Rewrites the first line of the heading with a new title, keeping its syntax.
+    pub fn retitle(&self, lines: &[String], title: &str) -> String {
+        let line = &lines[self.line];
+        match self.kind {
+            HeadingKind::Atx => format!("{} {title}", "#".repeat(self.level as usize)),
+            HeadingKind::Setext { .. } => title.to_owned(),
+            HeadingKind::Html => match html_heading(line) {
+                Some((_, open, close)) => format!("{}{title}{}", &line[..open], &line[close..]),
+                None => line.to_owned(),
+            },
+            HeadingKind::AsciiDoc => format!("{} {title}", "=".repeat(self.level as usize)),
+            HeadingKind::Rst { .. } => {
+                let indent = line.len() - line.trim_start().len();
+                format!("{}{title}", &line[..indent])
+            }
+            HeadingKind::Org => match org::parse_title(line) {
+                Some((_, _, "")) | None => format!("{} {title}", "*".repeat(self.level as usize)),
+                Some((_, _, tags)) => {
+                    format!("{} {title} {tags}", "*".repeat(self.level as usize))
+                }
+            },
+        }
+    }
+
+    /// Replaces the title of the heading in the document, extending the adornments of a
+    /// reStructuredText title to its new width.
+    pub fn set_title(&self, lines: &mut [String], title: &str) {
+        lines[self.line] = self.retitle(lines, title);
+        if let HeadingKind::Rst {
+            underline,
+            overline,
+        } = self.kind
+        {
+            rst::fit_adornments(lines, self.line, underline, overline);
+        }
+    }
+}
+
+/// Scans ATX, Setext and single-line HTML headings, skipping front matter, fenced and indented
+/// code blocks and HTML comments.
+pub fn parse_headings(lines: &[String]) -> Vec<Heading> {
+    let mut fence = FenceTracker::default();
+    let mut headings = Vec::new();
+    let mut ignoring = false;
+    let mut omit_next = false;
+    let mut in_comment = false;
+    // The first line of the pending paragraph, and whether it was marked to be omitted.
+    let mut paragraph: Option<(usize, bool)> = None;
+    let start = front_matter_end(lines).map_or(0, |end| end + 1);
+    for (index, line) in lines.iter().enumerate().skip(start) {
+        let omit = std::mem::take(&mut omit_next);
+        if in_comment {
+            in_comment = !line.contains("-->");
+            continue;
+        }
+        if fence.is_code(line) {
+            paragraph = None;
+            continue;
+        }
+        let trimmed = line.trim();
+        if trimmed.is_empty() {
+            paragraph = None;
+            continue;
+        }
+        if paragraph.is_none() && indent_width(line) >= 4 {
+            continue;
+        }
+        match trimmed {
+            IGNORE_START => ignoring = true,
+            IGNORE_END => ignoring = false,
+            _ if omit_comment().replace(trimmed, "").is_empty() => omit_next = true,
+            _ if opens_comment(trimmed) => in_comment = true,
+            _ => {}
+        }
+        if trimmed.starts_with("<!--") {
+            paragraph = None;
+            continue;
+        }
+        let (level, text, line_index, kind, omit) = if let Some((level, text)) = parse_atx(line) {
+            (level, text.to_owned(), index, HeadingKind::Atx, omit)
+        } else if let (Some(level), Some((start, omit))) = (setext_level(line), paragraph) {
+            let text = lines[start..index]
+                .iter()
+                .map(|line| line.trim())
+                .collect::<Vec<&str>>()
+                .join(" ");
+            let kind = HeadingKind::Setext { underline: index };
+            (level, text, start, kind, omit)
+        } else if let Some((level, open, close)) = html_heading(line) {
+            let text = line[open..close].trim().to_owned();
+            (level, text, index, HeadingKind::Html, omit)
+        } else {
+            if paragraph.is_none() && is_paragraph(trimmed) {
+                paragraph = Some((index, omit));
+            } else if !is_paragraph(trimmed) {
+                paragraph = None;
+            }
+            continue;
+        };
+        paragraph = None;
+        let omitted = omit_comment().is_match(&text);
+        let text = omit_comment().replace_all(&text, "");
+        let (text, mut id) = match (kind, id_attribute().captures(&text)) {
+            (HeadingKind::Html, _) | (_, None) => (text.trim().to_owned(), None),
+            (_, Some(caps)) => (
+                text[..caps.get(0).map_or(0, |m| m.start())]
+                    .trim()
+                    .to_owned(),
+                Some(caps[1].to_owned()),
+            ),
+        };
+        if kind == HeadingKind::Html {
+            id = html_id(&lines[line_index]);
+        }
+        if id.is_none() && line_index > start {
+            id = anchor_id(&lines[line_index - 1]);
+        }
+        headings.push(Heading {
+            level,
+            text,
+            line: line_index,
+            kind,
+            excluded: ignoring || omitted || omit,
+            id,
+        });
+    }
+    headings
+}
+
+fn omit_comment() -> &'static Regex {
+    static OMIT: OnceLock<Regex> = OnceLock::new();
+    OMIT.get_or_init(|| Regex::new(r"\s*<!--\s*omit (?:in|from) toc\s*-->").unwrap())
+}
+
+/// A trailing `{#id}`, possibly with other attributes such as `{#id .class}`.
+fn id_attribute() -> &'static Regex {
+    static ID: OnceLock<Regex> = OnceLock::new();
+    ID.get_or_init(|| Regex::new(r"\s*\{\s*#([^\s}]+)[^}]*\}\s*$").unwrap())
+}
+
+/// The `id` of the opening tag of an HTML heading.
+fn html_id(line: &str) -> Option<String> {
+    static ID: OnceLock<Regex> = OnceLock::new();
+    let id = ID.get_or_init(|| Regex::new(r#"^\s*<[hH][1-6]\s[^>]*?\bid="([^"]+)""#).unwrap());
+    Some(id.captures(line)?[1].to_owned())
+}
+
+/// The `id` or `name` of a line holding only an empty `<a>` tag.
+fn anchor_id(line: &str) -> Option<String> {
+    static ID: OnceLock<Regex> = OnceLock::new();
+    let id = ID.get_or_init(|| {
+        Regex::new(r#"^\s*<a\s+(?:id|name)="([^"]+)"\s*(?:/>|>\s*</a>)\s*$"#).unwrap()
+    });
+    Some(id.captures(line)?[1].to_owned())
+}
+
+fn opens_comment(line: &str) -> bool {
+    line.rfind("<!--")
+        .is_some_and(|open| !line[open..].contains("-->"))
+}
+
+fn setext_level(line: &str) -> Option<u8> {
+    let trimmed = strip_indent(line)?.trim_end();
+    if !trimmed.is_empty() && trimmed.chars().all(|c| c == '=') {
+        Some(1)
+    } else if !trimmed.is_empty() && trimmed.chars().all(|c| c == '-') {
+        Some(2)
+    } else {
+        None
+    }
+}
+
+/// Returns the level and the byte range of the content of a `<hN>...</hN>` line.
+fn html_heading(line: &str) -> Option<(u8, usize, usize)> {
+    static HTML: OnceLock<Regex> = OnceLock::new();
+    let html = HTML.get_or_init(|| {
+        Regex::new(r"^ {0,3}<[hH]([1-6])(?:\s[^>]*)?>(.*)</[hH]([1-6])>\s*$").unwrap()
+    });
+    let captures = html.captures(line)?;
+    if captures[1] != captures[3] {
+        return None;
+    }
+    let content = captures.get(2)?;
+    Some((captures[1].parse().ok()?, content.start(), content.end()))
+}
+
+/// Whether the line can be (a part of) the content of a Setext heading.
+fn is_paragraph(trimmed: &str) -> bool {
+    static BLOCK: OnceLock<Regex> = OnceLock::new();
+    let block = BLOCK.get_or_init(|| {
+        Regex::new(r"^(?:[-*+](?:\s|$)|\d{1,9}[.)](?:\s|$)|>|\||<|[-*_=](?:\s*[-*_=]){2,}\s*$)")
+            .unwrap()
+    });
+    !block.is_match(trimmed)
+}
+
+fn indent_width(line: &str) -> usize {
+    let mut width = 0;
+    for c in line.chars() {
+        match c {
+            ' ' => width += 1,
+            '\t' => width += 4 - width % 4,
+            _ => break,
+        }
+    }
+    width
+}
+
+/// Returns the index of the closing delimiter of a YAML (`---`) or TOML (`+++`) front matter.
+pub fn front_matter_end(lines: &[String]) -> Option<usize> {
+    let delimiter = match lines.first().map(|line| line.trim_end()) {
+        Some("---") => "---",
+        Some("+++") => "+++",
+        _ => return None,
+    };
+    lines
+        .iter()
+        .skip(1)
+        .position(|line| {
+            let line = line.trim_end();
+            line == delimiter || (delimiter == "---" && line == "...")
+        })
+        .map(|position| position + 1)
+}
+
+/// Tracks fenced code blocks while lines are fed in order.
+#[derive(Debug, Default)]
+pub struct FenceTracker {
+    fence: Option<(char, usize)>,
+}
+
+impl FenceTracker {
+    /// Whether the lines fed so far leave a fenced code block open.
+    pub fn in_block(&self) -> bool {
+        self.fence.is_some()
+    }
+
+    /// Returns `true` if the line is a fence or belongs to a fenced code block.
+    pub fn is_code(&mut self, line: &str) -> bool {
+        match self.fence {
+            Some((fence_char, fence_len)) => {
+                if is_closing_fence(line, fence_char, fence_len) {
+                    self.fence = None;
+                }
+                true
+            }
+            None => {
+                self.fence = opening_fence(line);
+                self.fence.is_some()
+            }
+        }
+    }
+}
+
+pub fn parse_atx(line: &str) -> Option<(u8, &str)> {
+    let trimmed = strip_indent(line)?;
+    let level = trimmed.chars().take_while(|&c| c == '#').count();
+    if level == 0 || level > 6 {
+        return None;
+    }
+    let rest = &trimmed[level..];
+    if !rest.is_empty() && !rest.starts_with([' ', '\t']) {
+        return None;
+    }
+    Some((level as u8, strip_closing_sequence(rest.trim())))
+}
+
+fn strip_closing_sequence(text: &str) -> &str {
+    let without_hashes = text.trim_end_matches('#');
+    if without_hashes.is_empty() {
+        return without_hashes;
+    }
+    if without_hashes.len() != text.len() && without_hashes.ends_with([' ', '\t']) {
+        without_hashes.trim_end()
+    } else {
+        text
+    }
+}
+
+fn strip_indent(line: &str) -> Option<&str> {
+    let indent = line.len() - line.trim_start_matches(' ').len();
+    if indent > 3 {
+        None
+    } else {
+        Some(&line[indent..])
+    }
+}
+
+fn opening_fence(line: &str) -> Option<(char, usize)> {
+    let trimmed = strip_indent(line)?;
+    let fence_char = trimmed.chars().next().filter(|&c| c == '`' || c == '~')?;
+    let fence_len = trimmed.chars().take_while(|&c| c == fence_char).count();
+    if fence_len < 3 {
+        return None;
+    }
+    if fence_char == '`' && trimmed[fence_len..].contains('`') {
+        return None;
+    }
+    Some((fence_char, fence_len))
+}
+
+fn is_closing_fence(line: &str, fence_char: char, fence_len: usize) -> bool {
+    match strip_indent(line) {
+        Some(trimmed) => {
+            let len = trimmed.chars().take_while(|&c| c == fence_char).count();
+            len >= fence_len && trimmed[len..].trim().is_empty()
+        }
+        None => false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn lines(content: &str) -> Vec<String> {
+        content.lines().map(str::to_owned).collect()
+    }
+
+    #[test]
+    fn test_parse_atx() {
+        assert_eq!(Some((1, "Title")), parse_atx("# Title"));
+        assert_eq!(Some((3, "Title")), parse_atx("   ### Title ###"));
+        assert_eq!(Some((2, "C#")), parse_atx("## C#"));
+        assert_eq!(Some((1, "")), parse_atx("#"));
+        assert_eq!(None, parse_atx("#Title"));
+        assert_eq!(None, parse_atx("####### Title"));
+        assert_eq!(None, parse_atx("    # Title"));
+    }
+
+    #[test]
+    fn test_parse_headings_skip_fenced_code() {
+        let content = lines("# A\n```rust\n# not heading\n```\n## B\n~~~~\n## C\n~~~\n~~~~\n### D");
+        let headings = parse_headings(&content);
+        let expected = vec![
+            Heading {
+                level: 1,
+                text: "A".to_owned(),
+                line: 0,
+                kind: HeadingKind::Atx,
+                excluded: false,
+                id: None,
+            },
+            Heading {
+                level: 2,
+                text: "B".to_owned(),
+                line: 4,
+                kind: HeadingKind::Atx,
+                excluded: false,
+                id: None,
+            },
+            Heading {
+                level: 3,
+                text: "D".to_owned(),
+                line: 9,
+                kind: HeadingKind::Atx,
+                excluded: false,
+                id: None,
+            },
+        ];
+        assert_eq!(expected, headings);
+    }
+
+    #[test]
+    fn test_parse_headings_exclusion() {
+        let content = lines(concat!(
+            "# A <!-- omit in toc -->\n",
+            "<!-- omit from toc -->\n",
+            "## B\n",
+            "## C\n",
+            "<!-- toc:ignore-start -->\n",
+            "### D\n",
+            "<!-- toc:ignore-end -->\n",
+            "### E",
+        ));
+        let headings = parse_headings(&content);
+        let headings: Vec<(&str, bool)> = headings
+            .iter()
+            .map(|heading| (heading.text.as_str(), heading.excluded))
+            .collect();
+        let expected = vec![
+            ("A", true),
+            ("B", true),
+            ("C", false),
+            ("D", true),
+            ("E", false),
+        ];
+        assert_eq!(expected, headings);
+    }
+
+    #[test]
+    fn test_parse_headings_setext_and_html() {
+        let content = lines(concat!(
+            "---\n",
+            "# not heading: front matter\n",
+            "---\n",
+            "Title\n",
+            "=====\n",
+            "Multi\n",
+            "line <!-- omit in toc -->\n",
+            "---\n",
+            "\n",
+            "---\n",
+            "- item\n",
+            "---\n",
+            "  <h3 id=\"x\">HTML <em>title</em></h3>\n",
+            "\n",
+            "    # indented code\n",
+            "<!--\n",
+            "# commented out\n",
+            "-->\n",
+            "Sub\n",
+            "---",
+        ));
+        let headings = parse_headings(&content);
+        let headings: Vec<(u8, &str, usize, HeadingKind, bool)> = headings
+            .iter()
+            .map(|h| (h.level, &h.text[..], h.line, h.kind, h.excluded))
+            .collect();
+        let expected = vec![
+            (1, "Title", 3, HeadingKind::Setext { underline: 4 }, false),
+            (
+                2,
+                "Multi line",
+                5,
+                HeadingKind::Setext { underline: 7 },
+                true,
+            ),
+            (3, "HTML <em>title</em>", 12, HeadingKind::Html, false),
+            (2, "Sub", 18, HeadingKind::Setext { underline: 19 }, false),
+        ];
+        assert_eq!(expected, headings);
+    }
+
+    #[test]
+    fn test_retitle() {
+        let content = lines("## A ##\nB\n---\n<h4 class=\"c\">C</h4>");
+        let retitled: Vec<String> = parse_headings(&content)
+            .iter()
+            .map(|heading| heading.retitle(&content, &format!("1 {}", heading.title(&content))))
+            .collect();
+        let expected = vec!["## 1 A", "1 B", "<h4 class=\"c\">1 C</h4>"];
+        assert_eq!(expected, retitled);
+    }
+}
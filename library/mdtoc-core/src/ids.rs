@@ -0,0 +1,110 @@
+use crate::heading::{parse_headings, HeadingKind, LevelRange};
+use crate::slug::{SlugStyle, Slugger};
+use crate::Error;
+use std::str::FromStr;
+
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+pub enum IdStyle {
+    /// `<a id="title"></a>` on the line before the heading, which every renderer understands.
+    #[default]
+    Anchor,
+    /// `## Title {#title}`, which GitHub and the other platforms show as text.
+    Attribute,
+}
+
+impl FromStr for IdStyle {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "anchor" => Ok(IdStyle::Anchor),
+            "attribute" => Ok(IdStyle::Attribute),
+            _ => Err(Error::UnknownValue {
+                kind: "id style",
+                value: s.to_owned(),
+                expected: "anchor, attribute",
+            }),
+        }
+    }
+}
+
+/// Writes the current anchor onto every listed heading without an explicit ID, so that it stays
+/// the same when the title changes.
+pub fn add_ids(
+    lines: &[String],
+    style: IdStyle,
+    slug_style: SlugStyle,
+    levels: LevelRange,
+) -> Vec<String> {
+    let headings = parse_headings(lines);
+    let mut slugger = Slugger::new(slug_style);
+    let anchors: Vec<String> = headings
+        .iter()
+        .map(|heading| heading.anchor(&mut slugger))
+        .collect();
+    let mut result = lines.to_vec();
+    for (heading, anchor) in headings.iter().zip(anchors).rev() {
+        if heading.id.is_some() || !heading.is_listed(levels) || anchor.is_empty() {
+            continue;
+        }
+        match (style, heading.kind) {
+            (IdStyle::Anchor, _) => {
+                result.insert(heading.line, format!("<a id=\"{anchor}\"></a>"));
+            }
+            (IdStyle::Attribute, HeadingKind::Atx) => {
+                let title = format!("{} {{#{anchor}}}", heading.title(lines));
+                result[heading.line] = heading.retitle(lines, &title);
+            }
+            (IdStyle::Attribute, HeadingKind::Setext { underline }) => {
+                let line = &mut result[underline - 1];
+                *line = format!("{} {{#{anchor}}}", line.trim_end());
+            }
+            (IdStyle::Attribute, HeadingKind::Html) => {
+                let line = &mut result[heading.line];
+                if let Some(open) = line.find('<') {
+                    line.insert_str(open + 3, &format!(" id=\"{anchor}\""));
+                }
+            }
+            // Only markdown headings are parsed here.
+            (IdStyle::Attribute, _) => {}
+        }
+    }
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn lines(content: &str) -> Vec<String> {
+        content.lines().map(str::to_owned).collect()
+    }
+
+    #[test]
+    fn test_add_ids() {
+        let content = lines(concat!(
+            "# Intro {#start}\n## Usage ##\nSetup\nGuide\n-----\n",
+            "<h3 class=\"x\">Notes</h3>\n<a id=\"kept\"></a>\n## Kept\n## Usage",
+        ));
+        let expected = lines(concat!(
+            "# Intro {#start}\n## Usage {#usage}\nSetup\nGuide {#setup-guide}\n-----\n",
+            "<h3 id=\"notes\" class=\"x\">Notes</h3>\n<a id=\"kept\"></a>\n## Kept\n",
+            "## Usage {#usage-1}",
+        ));
+        let levels = LevelRange::default();
+        let result = add_ids(&content, IdStyle::Attribute, SlugStyle::GitHub, levels);
+        assert_eq!(expected, result);
+        assert_eq!(
+            result,
+            add_ids(&result, IdStyle::Attribute, SlugStyle::GitHub, levels)
+        );
+
+        let content = lines("# A\n## B");
+        let expected = lines("# A\n<a id=\"b\"></a>\n## B");
+        let levels = LevelRange { min: 2, max: 6 };
+        assert_eq!(
+            expected,
+            add_ids(&content, IdStyle::Anchor, SlugStyle::GitHub, levels)
+        );
+    }
+}
@@ -0,0 +1,95 @@
+use crate::heading::FenceTracker;
+use crate::markup::FrontEnd;
+
+/// Inserts the TOC between the markers of the front end, or after the title if there are none.
+pub fn insert_toc(mut lines: Vec<String>, toc: &[String], front_end: &dyn FrontEnd) -> Vec<String> {
+    let mut block = front_end.toc_block(toc);
+    match find_markers(&lines, front_end) {
+        Some((start, stop)) => {
+            lines.splice(start..=stop, block);
+        }
+        None => {
+            let position = front_end.insert_position(&lines);
+            if position > 0 && !lines[position - 1].trim().is_empty() {
+                block.insert(0, String::new());
+            }
+            if lines
+                .get(position)
+                .is_some_and(|line| !line.trim().is_empty())
+            {
+                block.push(String::new());
+            }
+            lines.splice(position..position, block);
+        }
+    }
+    lines
+}
+
+/// Returns the lines of the start and stop markers of a TOC outside of code blocks. The stop
+/// marker pairs with the closest start marker before it, so that a start marker without a stop
+/// marker is left alone.
+pub fn find_markers(lines: &[String], front_end: &dyn FrontEnd) -> Option<(usize, usize)> {
+    let mut fence = FenceTracker::default();
+    let mut start = None;
+    for (index, line) in lines.iter().enumerate() {
+        if fence.is_code(line) {
+            continue;
+        }
+        match (start, front_end.marker(line)) {
+            (_, Some(true)) => start = Some(index),
+            (Some(start), Some(false)) => return Some((start, index)),
+            _ => {}
+        }
+    }
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::markup::Markup;
+
+    fn lines(content: &str) -> Vec<String> {
+        content.lines().map(str::to_owned).collect()
+    }
+
+    #[test]
+    fn test_insert_toc_after_front_matter_and_title() {
+        let content = lines("---\ntitle: t\n---\n# Title\nintro");
+        let result = insert_toc(
+            content,
+            &lines("- [Title](#title)"),
+            Markup::Markdown.front_end(),
+        );
+        let expected =
+            lines("---\ntitle: t\n---\n# Title\n\n<!-- toc -->\n- [Title](#title)\n<!-- tocstop -->\n\nintro");
+        assert_eq!(expected, result);
+    }
+
+    #[test]
+    fn test_insert_toc_replace_markers() {
+        let content =
+            lines("```\n<!-- toc -->\n```\n<!-- toc -->\n- old\n- old\n<!-- tocstop -->\ntext");
+        let result = insert_toc(content, &lines("- new"), Markup::Markdown.front_end());
+        let expected = lines("```\n<!-- toc -->\n```\n<!-- toc -->\n- new\n<!-- tocstop -->\ntext");
+        assert_eq!(expected, result);
+        assert_eq!(
+            expected,
+            insert_toc(result, &lines("- new"), Markup::Markdown.front_end())
+        );
+    }
+
+    #[test]
+    fn test_insert_toc_unterminated_marker() {
+        let content = lines("<!-- toc -->\n# T\ntext\n## A");
+        let toc = lines("- [A](#a)");
+        let result = insert_toc(content, &toc, Markup::Markdown.front_end());
+        let expected =
+            lines("<!-- toc -->\n# T\n\n<!-- toc -->\n- [A](#a)\n<!-- tocstop -->\n\ntext\n## A");
+        assert_eq!(expected, result);
+        assert_eq!(
+            expected,
+            insert_toc(result, &toc, Markup::Markdown.front_end())
+        );
+    }
+}
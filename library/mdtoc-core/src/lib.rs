@@ -0,0 +1,34 @@
+//! The TOC engine of mdtoc: a heading model shared by markdown, AsciiDoc, reStructuredText, Org
+//! and Jupyter notebooks, with slugging, title numbering and TOC insertion.
+//!
+//! ```
+//! use mdtoc_core::heading::LevelRange;
+//! use mdtoc_core::order::OrderStyle;
+//! use mdtoc_core::toc::TocOptions;
+//! use mdtoc_core::Document;
+//!
+//! let mut document = Document::parse("# Guide\n## Install\n");
+//! document.apply_numbering(OrderStyle::Decimal, LevelRange { min: 2, max: 6 });
+//! let options = TocOptions::default();
+//! assert_eq!("- [Guide](#guide)\n  - [1 Install](#1-install)\n", document.toc(&options));
+//! document.insert_toc(&options);
+//! assert!(document.render().contains("<!-- toc -->"));
+//! ```
+
+mod document;
+pub mod encoding;
+mod error;
+pub mod format;
+pub mod front_matter;
+pub mod heading;
+pub mod ids;
+pub mod insert;
+pub mod markup;
+pub mod order;
+pub mod slug;
+pub mod stats;
+pub mod text;
+pub mod toc;
+
+pub use document::Document;
+pub use error::Error;
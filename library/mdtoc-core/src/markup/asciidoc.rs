@@ -0,0 +1,200 @@
+use super::{render_list, FrontEnd};
+use crate::format::{self, TocFormat};
+use crate::heading::{Heading, HeadingKind};
+use crate::slug::plain_text;
+use crate::toc::{TocEntry, TocOptions};
+use regex::Regex;
+use std::sync::OnceLock;
+
+pub struct AsciiDoc;
+
+impl FrontEnd for AsciiDoc {
+    /// Scans `=` to `======` titles outside of delimited blocks and comments. The `=` document
+    /// title is level 1 and left out of the TOC and the numbering, like AsciiDoc does. Every
+    /// title gets the ID of a preceding `[[id]]` or `[#id]`, or else the ID AsciiDoc generates.
+    fn parse_headings(&self, lines: &[String]) -> Vec<Heading> {
+        let mut headings = Vec::new();
+        let mut delimiter: Option<&str> = None;
+        let mut anchor = None;
+        for (index, line) in lines.iter().enumerate() {
+            let line = line.trim_end();
+            if let Some(open) = delimiter {
+                if line == open {
+                    delimiter = None;
+                }
+                continue;
+            }
+            if is_delimiter(line) {
+                delimiter = Some(line);
+                anchor = None;
+                continue;
+            }
+            if line.starts_with("//") {
+                continue;
+            }
+            if let Some(id) = block_anchor(line) {
+                anchor = Some(id);
+                continue;
+            }
+            match parse_title(line) {
+                Some((level, text)) => headings.push(Heading {
+                    level,
+                    text: text.to_owned(),
+                    line: index,
+                    kind: HeadingKind::AsciiDoc,
+                    excluded: level == 1,
+                    id: Some(anchor.take().unwrap_or_else(|| auto_id(text))),
+                }),
+                // Other block attributes may stand between the anchor and the title.
+                None if !line.starts_with('[') => anchor = None,
+                None => {}
+            }
+        }
+        headings
+    }
+
+    fn render_toc(&self, outline: &[TocEntry], options: &TocOptions) -> Vec<String> {
+        if options.format != TocFormat::Markdown {
+            return format::render(outline, options);
+        }
+        let mut lines = Vec::new();
+        if let Some(title) = &options.title {
+            lines.push(format!(".{title}"));
+        }
+        render_list(&mut lines, outline, 1, &|entry, depth| {
+            let (slug, text, note) = (&entry.slug, &entry.text, entry.note_suffix());
+            format!("{} <<{slug},{text}>>{note}", "*".repeat(depth))
+        });
+        lines
+    }
+
+    fn markers(&self) -> (&'static str, &'static str) {
+        ("// toc", "// tocstop")
+    }
+
+    /// After the document header: the document title and the lines up to the first blank one.
+    fn insert_position(&self, lines: &[String]) -> usize {
+        let Some(title) = self
+            .parse_headings(lines)
+            .into_iter()
+            .find(|heading| heading.level == 1)
+        else {
+            return 0;
+        };
+        lines[title.line..]
+            .iter()
+            .position(|line| line.trim().is_empty())
+            .map_or(lines.len(), |blank| title.line + blank)
+    }
+}
+
+/// Returns the level and the title of a `== Title` line, with an optional closing `==`.
+pub fn parse_title(line: &str) -> Option<(u8, &str)> {
+    let level = line.chars().take_while(|&c| c == '=').count();
+    let rest = line[level..].strip_prefix([' ', '\t'])?;
+    if level == 0 || level > 6 {
+        return None;
+    }
+    let title = rest.trim();
+    let title = match title.strip_suffix(&line[..level]) {
+        Some(stripped) if stripped.ends_with([' ', '\t']) => stripped.trim_end(),
+        _ => title,
+    };
+    (!title.is_empty()).then_some((level as u8, title))
+}
+
+/// Whether the line opens or closes a delimited block, such as `----` or `////`.
+fn is_delimiter(line: &str) -> bool {
+    static DELIMITER: OnceLock<Regex> = OnceLock::new();
+    let delimiter = DELIMITER.get_or_init(|| {
+        Regex::new(r"^(?:-{4,}|\.{4,}|\+{4,}|/{4,}|={4,}|\*{4,}|_{4,}|--|```.*)$").unwrap()
+    });
+    delimiter.is_match(line)
+}
+
+/// The ID of an `[[id]]` or `[#id]` line.
+fn block_anchor(line: &str) -> Option<String> {
+    static ANCHOR: OnceLock<Regex> = OnceLock::new();
+    let anchor = ANCHOR.get_or_init(|| {
+        Regex::new(r"^\[(?:\[([^\],\s]+)(?:,[^\]]*)?\]|#([^\].%,\s]+)[^\]]*)\]$").unwrap()
+    });
+    let caps = anchor.captures(line)?;
+    Some(caps.get(1).or_else(|| caps.get(2))?.as_str().to_owned())
+}
+
+/// The ID AsciiDoc generates for a title with the default `_` prefix and separator.
+fn auto_id(title: &str) -> String {
+    let mut id = String::from("_");
+    for c in plain_text(title).to_lowercase().chars() {
+        if c.is_alphanumeric() || c == '_' {
+            id.push(c);
+        } else if matches!(c, ' ' | '-' | '.') && !id.ends_with('_') {
+            id.push('_');
+        }
+    }
+    while id.len() > 1 && id.ends_with('_') {
+        id.pop();
+    }
+    id
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::heading::LevelRange;
+    use crate::order::{self, OrderStyle};
+    use crate::toc::build_outline;
+
+    fn lines(content: &str) -> Vec<String> {
+        content.lines().map(str::to_owned).collect()
+    }
+
+    #[test]
+    fn test_parse_headings() {
+        let content = lines(concat!(
+            "= Guide\nAuthor\n\n== Install Guide ==\n----\n== not a title\n----\n",
+            "[[linux]]\n[source]\n=== On Linux\n// == comment\n==No title",
+        ));
+        let headings: Vec<(u8, String, Option<String>)> = AsciiDoc
+            .parse_headings(&content)
+            .into_iter()
+            .map(|heading| (heading.level, heading.text, heading.id))
+            .collect();
+        let expected = vec![
+            (1, "Guide".to_owned(), Some("_guide".to_owned())),
+            (
+                2,
+                "Install Guide".to_owned(),
+                Some("_install_guide".to_owned()),
+            ),
+            (3, "On Linux".to_owned(), Some("linux".to_owned())),
+        ];
+        assert_eq!(expected, headings);
+    }
+
+    #[test]
+    fn test_toc_and_numbering() {
+        let mut content = lines("= Guide\n:toc:\n\n== Install\n=== On Linux\n== Usage");
+        let headings = AsciiDoc.parse_headings(&content);
+        let outline = build_outline(&headings, Default::default(), LevelRange::default());
+        let expected = vec![
+            "* <<_install,Install>>",
+            "** <<_on_linux,On Linux>>",
+            "* <<_usage,Usage>>",
+        ];
+        assert_eq!(
+            expected,
+            AsciiDoc.render_toc(&outline, &TocOptions::default())
+        );
+        assert_eq!(2, AsciiDoc.insert_position(&content));
+
+        order::number_headings(
+            &mut content,
+            &headings,
+            OrderStyle::Dot,
+            LevelRange::default(),
+        );
+        let expected = lines("= Guide\n:toc:\n\n== 1. Install\n=== 1.1. On Linux\n== 2. Usage");
+        assert_eq!(expected, content);
+    }
+}
@@ -0,0 +1,47 @@
+use super::FrontEnd;
+use crate::format;
+use crate::heading::{front_matter_end, parse_headings, Heading};
+use crate::toc::{TocEntry, TocOptions};
+
+pub const TOC_START: &str = "<!-- toc -->";
+pub const TOC_STOP: &str = "<!-- tocstop -->";
+
+pub struct Markdown;
+
+impl FrontEnd for Markdown {
+    fn parse_headings(&self, lines: &[String]) -> Vec<Heading> {
+        parse_headings(lines)
+    }
+
+    fn render_toc(&self, outline: &[TocEntry], options: &TocOptions) -> Vec<String> {
+        format::render(outline, options)
+    }
+
+    fn markers(&self) -> (&'static str, &'static str) {
+        (TOC_START, TOC_STOP)
+    }
+
+    /// Accepts any spacing and case, and `<!-- /toc -->` as the stop marker.
+    fn marker(&self, line: &str) -> Option<bool> {
+        let comment = line
+            .trim()
+            .strip_prefix("<!--")?
+            .strip_suffix("-->")?
+            .trim()
+            .to_lowercase();
+        match &comment[..] {
+            "toc" => Some(true),
+            "tocstop" | "/toc" => Some(false),
+            _ => None,
+        }
+    }
+
+    /// After the first H1, or else after the front matter.
+    fn insert_position(&self, lines: &[String]) -> usize {
+        let front_matter = front_matter_end(lines).map_or(0, |end| end + 1);
+        parse_headings(lines)
+            .iter()
+            .find(|heading| heading.level == 1)
+            .map_or(front_matter, |heading| heading.end_line() + 1)
+    }
+}
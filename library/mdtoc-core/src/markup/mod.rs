@@ -0,0 +1,119 @@
+pub mod asciidoc;
+mod markdown;
+pub mod notebook;
+pub mod org;
+pub mod rst;
+
+use crate::heading::Heading;
+use crate::toc::{TocEntry, TocOptions};
+use crate::Error;
+use std::path::Path;
+use std::str::FromStr;
+
+/// The file extensions of every supported markup, used when walking directories.
+pub const EXTENSIONS: [&str; 9] = [
+    "md", "markdown", "mdx", "adoc", "asciidoc", "asc", "rst", "org", "ipynb",
+];
+
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+pub enum Markup {
+    #[default]
+    Markdown,
+    AsciiDoc,
+    /// reStructuredText.
+    Rst,
+    Org,
+    /// A Jupyter notebook, whose markdown cells are edited as one markdown document.
+    Notebook,
+}
+
+impl FromStr for Markup {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "markdown" | "md" => Ok(Markup::Markdown),
+            "asciidoc" | "adoc" => Ok(Markup::AsciiDoc),
+            "rst" => Ok(Markup::Rst),
+            "org" => Ok(Markup::Org),
+            "ipynb" | "notebook" => Ok(Markup::Notebook),
+            _ => Err(Error::UnknownValue {
+                kind: "markup",
+                value: s.to_owned(),
+                expected: "markdown, asciidoc, rst, org, ipynb",
+            }),
+        }
+    }
+}
+
+impl Markup {
+    /// Detects the markup from the file extension, defaulting to markdown.
+    pub fn detect(path: &Path) -> Markup {
+        match path.extension().and_then(|extension| extension.to_str()) {
+            Some("adoc" | "asciidoc" | "asc") => Markup::AsciiDoc,
+            Some("rst") => Markup::Rst,
+            Some("org") => Markup::Org,
+            Some("ipynb") => Markup::Notebook,
+            _ => Markup::Markdown,
+        }
+    }
+
+    pub fn front_end(self) -> &'static dyn FrontEnd {
+        match self {
+            Markup::Markdown | Markup::Notebook => &markdown::Markdown,
+            Markup::AsciiDoc => &asciidoc::AsciiDoc,
+            Markup::Rst => &rst::Rst,
+            Markup::Org => &org::Org,
+        }
+    }
+}
+
+/// Scans the headings of a markup into the common heading model, so that the TOC, numbering and
+/// insertion work the same on every markup, and writes TOCs back in its native syntax.
+pub trait FrontEnd: Sync {
+    fn parse_headings(&self, lines: &[String]) -> Vec<Heading>;
+
+    /// Renders the outline as a native list, or in `options.format` if another one is chosen.
+    fn render_toc(&self, outline: &[TocEntry], options: &TocOptions) -> Vec<String>;
+
+    /// The comment lines enclosing an inserted TOC.
+    fn markers(&self) -> (&'static str, &'static str);
+
+    /// Returns `Some(true)` for a start marker and `Some(false)` for a stop marker.
+    fn marker(&self, line: &str) -> Option<bool> {
+        let (start, stop) = self.markers();
+        match line.trim() {
+            line if line == start => Some(true),
+            line if line == stop => Some(false),
+            _ => None,
+        }
+    }
+
+    /// Returns the TOC enclosed in markers.
+    fn toc_block(&self, toc: &[String]) -> Vec<String> {
+        let (start, stop) = self.markers();
+        let mut block = vec![start.to_owned()];
+        block.extend_from_slice(toc);
+        block.push(stop.to_owned());
+        block
+    }
+
+    /// Returns the line a TOC is inserted at when there are no markers: after the first H1.
+    fn insert_position(&self, lines: &[String]) -> usize {
+        self.parse_headings(lines)
+            .iter()
+            .find(|heading| heading.level == 1)
+            .map_or(0, |heading| heading.end_line() + 1)
+    }
+}
+
+/// Renders a nested list, with `item` formatting an entry at a depth.
+fn render_list<F>(lines: &mut Vec<String>, entries: &[TocEntry], depth: usize, item: &F)
+where
+    F: Fn(&TocEntry, usize) -> String,
+{
+    for entry in entries {
+        lines.push(item(entry, depth));
+        render_list(lines, &entry.children, depth + 1, item);
+    }
+}
@@ -0,0 +1,126 @@
+use crate::Error;
+use serde::Serialize;
+use serde_json::ser::PrettyFormatter;
+use serde_json::Value;
+
+/// The line that separates the markdown cells of a notebook in its markdown document.
+const CELL_BREAK: &str = "<!-- mdtoc:cell -->";
+
+/// A Jupyter notebook, whose markdown cells are edited as one markdown document.
+#[derive(Debug)]
+pub struct Notebook {
+    json: Value,
+}
+
+impl Notebook {
+    pub fn parse(content: &str) -> Result<Notebook, Error> {
+        let json: Value =
+            serde_json::from_str(content).map_err(|e| Error::InvalidNotebook(e.to_string()))?;
+        if !json.get("cells").is_some_and(Value::is_array) {
+            return Err(Error::InvalidNotebook("no `cells` array".to_owned()));
+        }
+        Ok(Notebook { json })
+    }
+
+    /// Joins the sources of the markdown cells, separated by a marker line.
+    pub fn markdown(&self) -> Vec<String> {
+        let mut lines = Vec::new();
+        for (index, cell) in self.markdown_cells().enumerate() {
+            if index > 0 {
+                lines.push(CELL_BREAK.to_owned());
+            }
+            lines.extend(source(cell).lines().map(str::to_owned));
+        }
+        lines
+    }
+
+    /// Writes the edited markdown document back into the markdown cells, in the indentation
+    /// Jupyter saves notebooks with. Cells keep the form of their source, a string or a list of
+    /// lines, and its trailing newline, and unchanged cells are left as they are.
+    pub fn render(&self, lines: &[String]) -> String {
+        let mut json = self.json.clone();
+        let mut sources = lines.split(|line| line == CELL_BREAK);
+        for cell in markdown_cells_mut(&mut json) {
+            let previous = source(cell);
+            let mut text = sources.next().unwrap_or_default().join("\n");
+            if previous.ends_with('\n') {
+                text.push('\n');
+            }
+            if text == previous {
+                continue;
+            }
+            cell["source"] = match cell["source"] {
+                Value::String(_) => Value::String(text),
+                _ => Value::Array(
+                    text.split_inclusive('\n')
+                        .map(|line| Value::String(line.to_owned()))
+                        .collect(),
+                ),
+            };
+        }
+        let mut output = Vec::new();
+        let mut serializer =
+            serde_json::Serializer::with_formatter(&mut output, PrettyFormatter::with_indent(b" "));
+        json.serialize(&mut serializer)
+            .expect("a JSON value serializes");
+        let mut output = String::from_utf8(output).expect("JSON is UTF-8");
+        output.push('\n');
+        output
+    }
+
+    fn markdown_cells(&self) -> impl Iterator<Item = &Value> {
+        self.json["cells"]
+            .as_array()
+            .into_iter()
+            .flatten()
+            .filter(|cell| cell["cell_type"] == "markdown")
+    }
+}
+
+/// The source of a cell, which is saved either as a string or as a list of lines.
+fn source(cell: &Value) -> String {
+    match cell.get("source") {
+        Some(Value::Array(parts)) => parts.iter().filter_map(Value::as_str).collect(),
+        Some(Value::String(source)) => source.clone(),
+        _ => String::new(),
+    }
+}
+
+fn markdown_cells_mut(json: &mut Value) -> impl Iterator<Item = &mut Value> {
+    json["cells"]
+        .as_array_mut()
+        .into_iter()
+        .flatten()
+        .filter(|cell| cell["cell_type"] == "markdown")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_round_trip() {
+        let content = concat!(
+            "{\n \"cells\": [\n  {\n   \"cell_type\": \"markdown\",\n   \"metadata\": {},\n",
+            "   \"source\": [\n    \"# Guide\\n\",\n    \"\\n\",\n    \"## Install\\n\"\n   ]\n  },\n",
+            "  {\n   \"cell_type\": \"code\",\n   \"source\": \"# not a heading\"\n  },\n",
+            "  {\n   \"cell_type\": \"markdown\",\n   \"source\": \"## Usage\"\n  }\n ]\n}\n",
+        );
+        let notebook = Notebook::parse(content).unwrap();
+        let mut lines = notebook.markdown();
+        assert_eq!(
+            vec!["# Guide", "", "## Install", CELL_BREAK, "## Usage"],
+            lines
+        );
+        assert_eq!(content, notebook.render(&lines));
+
+        lines.insert(1, "- [Install](#install)".to_owned());
+        let rendered = notebook.render(&lines);
+        assert!(rendered
+            .contains("   \"source\": [\n    \"# Guide\\n\",\n    \"- [Install](#install)\\n\","));
+        assert!(rendered.contains("    \"## Install\\n\"\n   ]"));
+        assert!(rendered.contains("\"source\": \"# not a heading\""));
+        assert!(rendered.contains("\"source\": \"## Usage\""));
+        assert!(Notebook::parse("[]").is_err());
+    }
+}
@@ -0,0 +1,155 @@
+use super::{render_list, FrontEnd};
+use crate::format::{self, TocFormat};
+use crate::heading::{Heading, HeadingKind};
+use crate::toc::{TocEntry, TocOptions};
+use regex::Regex;
+use std::sync::OnceLock;
+
+pub struct Org;
+
+impl FrontEnd for Org {
+    /// Scans `*` headlines up to level 6 outside of `#+begin_` blocks. Headlines tagged
+    /// `:noexport:` are left out of the TOC, and a `:CUSTOM_ID:` property is their ID.
+    fn parse_headings(&self, lines: &[String]) -> Vec<Heading> {
+        let mut headings = Vec::new();
+        let mut in_block = false;
+        for (index, line) in lines.iter().enumerate() {
+            let keyword = line.trim_start().to_lowercase();
+            if in_block {
+                in_block = !keyword.starts_with("#+end_");
+                continue;
+            }
+            if keyword.starts_with("#+begin_") {
+                in_block = true;
+                continue;
+            }
+            let Some((level, text, tags)) = parse_title(line) else {
+                continue;
+            };
+            headings.push(Heading {
+                level,
+                text: text.to_owned(),
+                line: index,
+                kind: HeadingKind::Org,
+                excluded: tags.split(':').any(|tag| tag == "noexport"),
+                id: custom_id(&lines[index + 1..]),
+            });
+        }
+        headings
+    }
+
+    /// Links to the `CUSTOM_ID` of a headline, or else to its title.
+    fn render_toc(&self, outline: &[TocEntry], options: &TocOptions) -> Vec<String> {
+        if options.format != TocFormat::Markdown {
+            return format::render(outline, options);
+        }
+        let mut lines = Vec::new();
+        if let Some(title) = &options.title {
+            lines.push(format!("*{title}*"));
+            lines.push(String::new());
+        }
+        render_list(&mut lines, outline, 0, &|entry, depth| {
+            let target = match &entry.id {
+                Some(id) => format!("#{id}"),
+                None => format!("*{}", entry.text),
+            };
+            let (text, note) = (&entry.text, entry.note_suffix());
+            format!("{}- [[{target}][{text}]]{note}", "  ".repeat(depth))
+        });
+        lines
+    }
+
+    fn markers(&self) -> (&'static str, &'static str) {
+        ("# toc", "# tocstop")
+    }
+
+    /// Before the first headline, since Org titles the document with `#+title:`.
+    fn insert_position(&self, lines: &[String]) -> usize {
+        self.parse_headings(lines)
+            .first()
+            .map_or(lines.len(), |heading| heading.line)
+    }
+}
+
+/// Returns the level, the title and the tags of a headline.
+pub fn parse_title(line: &str) -> Option<(u8, &str, &str)> {
+    static TAGS: OnceLock<Regex> = OnceLock::new();
+    let tags = TAGS.get_or_init(|| Regex::new(r"\s+(:[\w@#%:]+:)\s*$").unwrap());
+    let level = line.chars().take_while(|&c| c == '*').count();
+    let rest = line[level..].strip_prefix(' ')?;
+    if level == 0 || level > 6 {
+        return None;
+    }
+    match tags.captures(rest) {
+        Some(caps) => {
+            let start = caps.get(0).map_or(rest.len(), |m| m.start());
+            Some((level as u8, rest[..start].trim(), caps.get(1)?.as_str()))
+        }
+        None => Some((level as u8, rest.trim(), "")),
+    }
+}
+
+/// The `CUSTOM_ID` of the property drawer that follows a headline.
+fn custom_id(lines: &[String]) -> Option<String> {
+    let mut lines = lines.iter().map(|line| line.trim());
+    let mut first = lines.next()?;
+    if ["SCHEDULED:", "DEADLINE:", "CLOSED:"]
+        .iter()
+        .any(|planning| first.starts_with(planning))
+    {
+        first = lines.next()?;
+    }
+    if !first.eq_ignore_ascii_case(":properties:") {
+        return None;
+    }
+    for line in lines.take_while(|line| !line.eq_ignore_ascii_case(":end:")) {
+        let Some((key, value)) = line.strip_prefix(':').and_then(|line| line.split_once(':'))
+        else {
+            continue;
+        };
+        if key.eq_ignore_ascii_case("custom_id") && !value.trim().is_empty() {
+            return Some(value.trim().to_owned());
+        }
+    }
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::heading::LevelRange;
+    use crate::order::{self, OrderStyle};
+    use crate::toc::build_outline;
+
+    fn lines(content: &str) -> Vec<String> {
+        content.lines().map(str::to_owned).collect()
+    }
+
+    #[test]
+    fn test_toc_and_numbering() {
+        let mut content = lines(concat!(
+            "#+title: Guide\n\n* Install    :setup:\n:PROPERTIES:\n:CUSTOM_ID: install\n:END:\n",
+            "#+begin_src org\n* not a headline\n#+end_src\n** On Linux\n* Notes :noexport:\n* Usage",
+        ));
+        let headings = Org.parse_headings(&content);
+        let outline = build_outline(&headings, Default::default(), LevelRange::default());
+        let expected = vec![
+            "- [[#install][Install]]",
+            "  - [[*On Linux][On Linux]]",
+            "- [[*Usage][Usage]]",
+        ];
+        assert_eq!(expected, Org.render_toc(&outline, &TocOptions::default()));
+        assert_eq!(2, Org.insert_position(&content));
+
+        order::number_headings(
+            &mut content,
+            &headings,
+            OrderStyle::Decimal,
+            LevelRange::default(),
+        );
+        assert_eq!("* 1 Install :setup:", content[2]);
+        assert_eq!("** 1.1 On Linux", content[9]);
+        assert_eq!("* Notes :noexport:", content[10]);
+        assert_eq!("* 2 Usage", content[11]);
+    }
+}
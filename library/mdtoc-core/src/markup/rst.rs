@@ -0,0 +1,229 @@
+use super::FrontEnd;
+use crate::format::{self, TocFormat};
+use crate::heading::{Heading, HeadingKind};
+use crate::text::display_width;
+use crate::toc::{TocEntry, TocOptions};
+
+const ADORNMENTS: &str = "!\"#$%&'()*+,-./:;<=>?@[\\]^_`{|}~";
+
+pub struct Rst;
+
+impl FrontEnd for Rst {
+    /// Scans titles with an underline, or an overline and an underline, of a punctuation
+    /// character. Levels follow the order in which the adornment styles first appear, like
+    /// docutils does, and a lone level 1 title is left out as the document title. A title gets
+    /// the ID of a preceding `.. _label:` target, or else the ID docutils generates.
+    fn parse_headings(&self, lines: &[String]) -> Vec<Heading> {
+        let mut headings = Vec::new();
+        let mut styles: Vec<(char, bool)> = Vec::new();
+        let mut index = 0;
+        while index + 1 < lines.len() {
+            let blank_before = index == 0 || lines[index - 1].trim().is_empty();
+            let overlined = adornment(&lines[index])
+                .filter(|_| blank_before && index + 2 < lines.len())
+                .filter(|_| lines[index + 2].trim_end() == lines[index].trim_end())
+                .filter(|_| is_title(&lines[index + 1]));
+            let (line, underline, style) = if let Some(c) = overlined {
+                (index + 1, index + 2, (c, true))
+            } else if let Some(c) = adornment(&lines[index + 1]).filter(|_| {
+                blank_before
+                    && is_title(&lines[index])
+                    && !lines[index].starts_with(char::is_whitespace)
+                    && fits(&lines[index + 1], &lines[index])
+            }) {
+                (index, index + 1, (c, false))
+            } else {
+                index += 1;
+                continue;
+            };
+            let level = match styles.iter().position(|known| *known == style) {
+                Some(position) => position + 1,
+                None => {
+                    styles.push(style);
+                    styles.len()
+                }
+            };
+            let text = lines[line].trim().to_owned();
+            if level <= 6 {
+                headings.push(Heading {
+                    level: level as u8,
+                    id: Some(
+                        target(&lines[..line - usize::from(style.1)])
+                            .unwrap_or_else(|| make_id(&text)),
+                    ),
+                    text,
+                    line,
+                    kind: HeadingKind::Rst {
+                        underline,
+                        overline: style.1,
+                    },
+                    excluded: false,
+                });
+            }
+            index = underline + 1;
+        }
+        // A lone title of the first style is the document title, like docutils promotes it.
+        if headings.iter().filter(|heading| heading.level == 1).count() == 1 {
+            headings[0].excluded = true;
+        }
+        headings
+    }
+
+    /// Refers to the titles, which are implicit hyperlink targets.
+    fn render_toc(&self, outline: &[TocEntry], options: &TocOptions) -> Vec<String> {
+        if options.format != TocFormat::Markdown {
+            return format::render(outline, options);
+        }
+        let mut lines = Vec::new();
+        if let Some(title) = &options.title {
+            lines.push(format!("**{title}**"));
+            lines.push(String::new());
+        }
+        list(&mut lines, outline, 0);
+        lines
+    }
+
+    fn markers(&self) -> (&'static str, &'static str) {
+        (".. toc", ".. tocstop")
+    }
+
+    /// Separates the markers, which are comments, from the list by blank lines.
+    fn toc_block(&self, toc: &[String]) -> Vec<String> {
+        let (start, stop) = self.markers();
+        let mut block = vec![start.to_owned(), String::new()];
+        block.extend_from_slice(toc);
+        block.push(String::new());
+        block.push(stop.to_owned());
+        block
+    }
+}
+
+/// Extends the underline, and the overline, of a title to its width after a change.
+pub fn fit_adornments(lines: &mut [String], line: usize, underline: usize, overline: bool) {
+    let title = lines[line].trim_end();
+    // An overlined title may be inset, with the adornments extending past both of its ends.
+    let inset = title.len() - title.trim_start().len();
+    let width = display_width(title) + inset;
+    let mut adornments = vec![underline];
+    if overline {
+        adornments.push(line - 1);
+    }
+    for adornment in adornments {
+        let current = lines[adornment].trim_end();
+        let Some(c) = current.chars().next() else {
+            continue;
+        };
+        let len = current.chars().count().max(width);
+        lines[adornment] = c.to_string().repeat(len);
+    }
+}
+
+/// Renders a bullet list, with blank lines around nested lists as reStructuredText requires.
+fn list(lines: &mut Vec<String>, entries: &[TocEntry], depth: usize) {
+    for entry in entries {
+        let (text, note) = (&entry.text, entry.note_suffix());
+        lines.push(format!("{}- `{text}`_{note}", "  ".repeat(depth)));
+        if !entry.children.is_empty() {
+            lines.push(String::new());
+            list(lines, &entry.children, depth + 1);
+            if lines.last().is_some_and(|line| !line.is_empty()) {
+                lines.push(String::new());
+            }
+        }
+    }
+    if depth == 0 && lines.last().is_some_and(|line| line.is_empty()) {
+        lines.pop();
+    }
+}
+
+/// Returns the character of a line made of one repeated punctuation character.
+fn adornment(line: &str) -> Option<char> {
+    let line = line.trim_end();
+    let c = line.chars().next().filter(|c| ADORNMENTS.contains(*c))?;
+    (line.chars().count() >= 2 && line.chars().all(|other| other == c)).then_some(c)
+}
+
+fn is_title(line: &str) -> bool {
+    !line.trim().is_empty() && adornment(line).is_none()
+}
+
+/// Whether an underline is long enough for the title, which docutils also accepts from four
+/// characters on.
+fn fits(underline: &str, title: &str) -> bool {
+    let len = underline.trim_end().chars().count();
+    len >= display_width(title.trim_end()) || len >= 4
+}
+
+/// The label of a `.. _label:` target right before the title, separated by blank lines at most.
+fn target(before: &[String]) -> Option<String> {
+    let line = before.iter().rev().find(|line| !line.trim().is_empty())?;
+    let label = line.trim().strip_prefix(".. _")?.strip_suffix(':')?;
+    Some(make_id(label))
+}
+
+/// The ID docutils generates: lowercase ASCII letters and digits joined by `-`.
+fn make_id(text: &str) -> String {
+    let mut id = String::new();
+    for c in text.to_lowercase().chars() {
+        if c.is_ascii_alphanumeric() {
+            id.push(c);
+        } else if !id.is_empty() && !id.ends_with('-') {
+            id.push('-');
+        }
+    }
+    id.trim_end_matches('-').to_owned()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::heading::LevelRange;
+    use crate::order::{self, OrderStyle};
+    use crate::toc::build_outline;
+
+    fn lines(content: &str) -> Vec<String> {
+        content.lines().map(str::to_owned).collect()
+    }
+
+    #[test]
+    fn test_parse_headings() {
+        let content = lines(concat!(
+            "=====\nGuide\n=====\n\nIntro\n\nInstall\n-------\n\n.. _setup-linux:\n\n",
+            "On Linux\n~~~~~~~~\n\n----\n\nUsage\n-----\n\n::\n\n  Code\n  ----",
+        ));
+        let headings: Vec<(u8, &str, usize, Option<String>)> = Rst
+            .parse_headings(&content)
+            .iter()
+            .map(|heading| {
+                (
+                    heading.level,
+                    &content[heading.line][..],
+                    heading.end_line(),
+                    heading.id.clone(),
+                )
+            })
+            .collect();
+        let expected = vec![
+            (1, "Guide", 2, Some("guide".to_owned())),
+            (2, "Install", 7, Some("install".to_owned())),
+            (3, "On Linux", 12, Some("setup-linux".to_owned())),
+            (2, "Usage", 17, Some("usage".to_owned())),
+        ];
+        assert_eq!(expected, headings);
+    }
+
+    #[test]
+    fn test_toc_and_numbering() {
+        let mut content =
+            lines("=====\nGuide\n=====\n\nInstall\n-------\n\nLinux\n~~~~~\n\nUsage\n-----");
+        let headings = Rst.parse_headings(&content);
+        let levels = LevelRange { min: 2, max: 6 };
+        let outline = build_outline(&headings, Default::default(), levels);
+        let expected = vec!["- `Install`_", "", "  - `Linux`_", "", "- `Usage`_"];
+        assert_eq!(expected, Rst.render_toc(&outline, &TocOptions::default()));
+
+        order::number_headings(&mut content, &headings, OrderStyle::Decimal, levels);
+        let expected = lines("=====\nGuide\n=====\n\n1 Install\n---------\n\n1.1 Linux\n~~~~~~~~~\n\n2 Usage\n-------");
+        assert_eq!(expected, content);
+    }
+}
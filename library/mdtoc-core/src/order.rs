@@ -0,0 +1,331 @@
+use crate::heading::{parse_headings, Heading, LevelRange};
+use crate::Error;
+use regex::Regex;
+use std::str::FromStr;
+use std::sync::OnceLock;
+
+const CHINESE_DIGITS: [&str; 10] = ["零", "一", "二", "三", "四", "五", "六", "七", "八", "九"];
+const CIRCLED_NUMBERS: &str = "①②③④⑤⑥⑦⑧⑨⑩⑪⑫⑬⑭⑮⑯⑰⑱⑲⑳";
+
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+pub enum OrderStyle {
+    /// `1`, `1.1`, `1.1.1`
+    #[default]
+    Decimal,
+    /// `1.`, `1.1.`, `1.1.1.`
+    Dot,
+    /// `I.`, `I.1`, `I.1.1`
+    Roman,
+    /// `一、`, `（一）`, `1.`, `（1）`, `①`
+    Chinese,
+}
+
+impl FromStr for OrderStyle {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "decimal" => Ok(OrderStyle::Decimal),
+            "dot" => Ok(OrderStyle::Dot),
+            "roman" => Ok(OrderStyle::Roman),
+            "chinese" => Ok(OrderStyle::Chinese),
+            _ => Err(Error::UnknownValue {
+                kind: "order style",
+                value: s.to_owned(),
+                expected: "decimal, dot, roman, chinese",
+            }),
+        }
+    }
+}
+
+impl OrderStyle {
+    const ALL: [OrderStyle; 4] = [
+        OrderStyle::Decimal,
+        OrderStyle::Dot,
+        OrderStyle::Roman,
+        OrderStyle::Chinese,
+    ];
+
+    /// The length of the number the style gives a heading at a depth, 1 for the top, if its
+    /// title starts with one. Numbers above `max` are part of the title rather than an order.
+    fn prefix_len(&self, title: &str, depth: usize, max: usize) -> Option<usize> {
+        let captures = self.pattern().captures(title)?;
+        let numbers: Vec<usize> = match captures.name("numbers") {
+            Some(numbers) => numbers
+                .as_str()
+                .split('.')
+                .map(|number| number.parse().unwrap_or(usize::MAX))
+                .collect(),
+            None => Vec::new(),
+        };
+        let (found, depth) = match self {
+            OrderStyle::Decimal | OrderStyle::Dot => (numbers.len(), depth),
+            OrderStyle::Roman => (numbers.len() + 1, depth),
+            // The circled numbers are used from the fifth depth on.
+            OrderStyle::Chinese => {
+                let found = (1..=5).find(|depth| captures.name(&format!("depth{depth}")).is_some());
+                (found?, depth.min(5))
+            }
+        };
+        let in_range = numbers.iter().all(|number| (1..=max).contains(number));
+        (found == depth && in_range).then(|| captures[0].len())
+    }
+
+    fn pattern(&self) -> &'static Regex {
+        static DECIMAL: OnceLock<Regex> = OnceLock::new();
+        static DOT: OnceLock<Regex> = OnceLock::new();
+        static ROMAN: OnceLock<Regex> = OnceLock::new();
+        static CHINESE: OnceLock<Regex> = OnceLock::new();
+        let (pattern, lock) = match self {
+            OrderStyle::Decimal => (r"^(?<numbers>\d+(?:\.\d+)*)\s+", &DECIMAL),
+            OrderStyle::Dot => (r"^(?<numbers>\d+(?:\.\d+)*)\.\s+", &DOT),
+            OrderStyle::Roman => (r"^[IVXLCDM]+(?:\.|\.(?<numbers>\d+(?:\.\d+)*))\s+", &ROMAN),
+            OrderStyle::Chinese => (
+                concat!(
+                    r"^(?:(?<depth1>[零一二三四五六七八九十百]+、)",
+                    r"|(?<depth2>（[零一二三四五六七八九十百]+）)",
+                    r"|(?<depth3>(?<numbers>\d+)\.\s)",
+                    r"|(?<depth4>（\d+）)",
+                    r"|(?<depth5>[①-⑳]\s|\(\d+\)\s))\s*",
+                ),
+                &CHINESE,
+            ),
+        };
+        lock.get_or_init(|| Regex::new(pattern).unwrap())
+    }
+
+    fn format(&self, numbers: &[usize]) -> String {
+        match self {
+            OrderStyle::Decimal => format!("{} ", join_decimal(numbers)),
+            OrderStyle::Dot => format!("{}. ", join_decimal(numbers)),
+            OrderStyle::Roman => match numbers.split_first() {
+                Some((first, [])) => format!("{}. ", to_roman(*first)),
+                Some((first, rest)) => format!("{}.{} ", to_roman(*first), join_decimal(rest)),
+                None => String::new(),
+            },
+            OrderStyle::Chinese => {
+                let number = *numbers.last().unwrap_or(&0);
+                match numbers.len() {
+                    1 => format!("{}、", to_chinese(number)),
+                    2 => format!("（{}）", to_chinese(number)),
+                    3 => format!("{number}. "),
+                    4 => format!("（{number}）"),
+                    _ => match CIRCLED_NUMBERS.chars().nth(number.wrapping_sub(1)) {
+                        Some(circled) => format!("{circled} "),
+                        None => format!("({number}) "),
+                    },
+                }
+            }
+        }
+    }
+}
+
+pub fn apply_title_order(lines: &mut [String], style: OrderStyle, levels: LevelRange) {
+    let headings = parse_headings(lines);
+    number_headings(lines, &headings, style, levels);
+}
+
+/// Numbers the given headings of the document, scanned by any front end. Existing numbers are
+/// only replaced when most listed headings carry one in the same style, at their depth, so that
+/// titles such as `2024 Roadmap` keep their leading number.
+pub fn number_headings(
+    lines: &mut [String],
+    headings: &[Heading],
+    style: OrderStyle,
+    levels: LevelRange,
+) {
+    let min_level = match headings
+        .iter()
+        .filter(|heading| heading.is_listed(levels))
+        .map(|heading| heading.level)
+        .min()
+    {
+        Some(level) => level,
+        None => return,
+    };
+    let mut numbered = Vec::new();
+    let mut numbers: Vec<usize> = Vec::new();
+    for heading in headings {
+        if heading.level < min_level && !heading.excluded {
+            numbers.clear();
+        }
+        if !heading.is_listed(levels) {
+            continue;
+        }
+        let depth = (heading.level - min_level) as usize;
+        numbers.resize(depth + 1, 0);
+        numbers[depth] += 1;
+        numbered.push((heading, numbers.clone()));
+    }
+    let previous = previous_style(lines, &numbered);
+    for (heading, numbers) in numbered {
+        let title = heading.title(lines);
+        let prefix_len = previous
+            .and_then(|previous| previous.prefix_len(title, numbers.len(), headings.len()))
+            .unwrap_or(0);
+        let title = format!("{}{}", style.format(&numbers), &title[prefix_len..]);
+        heading.set_title(lines, &title);
+    }
+}
+
+/// The style of the numbers the headings already carry, if most of them do.
+fn previous_style(lines: &[String], numbered: &[(&Heading, Vec<usize>)]) -> Option<OrderStyle> {
+    let mut best = None;
+    let mut best_count = 0;
+    for style in OrderStyle::ALL {
+        let count = numbered
+            .iter()
+            .filter(|(heading, numbers)| {
+                let title = heading.title(lines);
+                style
+                    .prefix_len(title, numbers.len(), numbered.len())
+                    .is_some()
+            })
+            .count();
+        if count > best_count {
+            best = Some(style);
+            best_count = count;
+        }
+    }
+    best.filter(|_| best_count * 2 > numbered.len())
+}
+
+fn join_decimal(numbers: &[usize]) -> String {
+    numbers
+        .iter()
+        .map(usize::to_string)
+        .collect::<Vec<String>>()
+        .join(".")
+}
+
+fn to_roman(mut number: usize) -> String {
+    const SYMBOLS: [(usize, &str); 13] = [
+        (1000, "M"),
+        (900, "CM"),
+        (500, "D"),
+        (400, "CD"),
+        (100, "C"),
+        (90, "XC"),
+        (50, "L"),
+        (40, "XL"),
+        (10, "X"),
+        (9, "IX"),
+        (5, "V"),
+        (4, "IV"),
+        (1, "I"),
+    ];
+    let mut roman = String::new();
+    for (value, symbol) in SYMBOLS {
+        while number >= value {
+            roman.push_str(symbol);
+            number -= value;
+        }
+    }
+    roman
+}
+
+fn to_chinese(number: usize) -> String {
+    match number {
+        0..=9 => CHINESE_DIGITS[number].to_owned(),
+        10..=19 => format!("十{}", digit_or_empty(number % 10)),
+        20..=99 => format!(
+            "{}十{}",
+            CHINESE_DIGITS[number / 10],
+            digit_or_empty(number % 10)
+        ),
+        _ => number.to_string(),
+    }
+}
+
+fn digit_or_empty(digit: usize) -> &'static str {
+    if digit == 0 {
+        ""
+    } else {
+        CHINESE_DIGITS[digit]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn lines(content: &str) -> Vec<String> {
+        content.lines().map(str::to_owned).collect()
+    }
+
+    #[test]
+    fn test_apply_title_order() {
+        let mut content = lines("# H1\n## H2\n### H3\n## H2\n# H1\n### H3");
+        apply_title_order(&mut content, OrderStyle::Decimal, LevelRange::default());
+        let expected = lines("# 1 H1\n## 1.1 H2\n### 1.1.1 H3\n## 1.2 H2\n# 2 H1\n### 2.0.1 H3");
+        assert_eq!(expected, content);
+    }
+
+    #[test]
+    fn test_apply_title_order_again() {
+        let mut content = lines("## H2\n### 2.1 H3\n## 2 H2");
+        apply_title_order(&mut content, OrderStyle::Dot, LevelRange::default());
+        let expected = lines("## 1. H2\n### 1.1. H3\n## 2. H2");
+        assert_eq!(expected, content);
+        for style in [
+            OrderStyle::Roman,
+            OrderStyle::Chinese,
+            OrderStyle::Decimal,
+            OrderStyle::Dot,
+        ] {
+            apply_title_order(&mut content, style, LevelRange::default());
+        }
+        assert_eq!(expected, content);
+    }
+
+    #[test]
+    fn test_apply_title_order_leading_numbers() {
+        let mut content = lines("# Plan\n## 2024 Roadmap\n## 404 Handling\n### 3 Ways to Install");
+        apply_title_order(&mut content, OrderStyle::Decimal, LevelRange::default());
+        let expected = lines(
+            "# 1 Plan\n## 1.1 2024 Roadmap\n## 1.2 404 Handling\n### 1.2.1 3 Ways to Install",
+        );
+        assert_eq!(expected, content);
+        apply_title_order(&mut content, OrderStyle::Decimal, LevelRange::default());
+        assert_eq!(expected, content);
+
+        // A minority of numbers at the right depth is not a previous numbering.
+        let mut content = lines("# 1 Intro\n# Setup\n# Usage");
+        apply_title_order(&mut content, OrderStyle::Dot, LevelRange::default());
+        assert_eq!(lines("# 1. 1 Intro\n# 2. Setup\n# 3. Usage"), content);
+        let mut content = lines("# 2024 Plans\n# 404 Errors");
+        apply_title_order(&mut content, OrderStyle::Decimal, LevelRange::default());
+        assert_eq!(lines("# 1 2024 Plans\n# 2 404 Errors"), content);
+    }
+
+    #[test]
+    fn test_apply_title_order_filtered() {
+        let mut content =
+            lines("# T\n## A\n### B\n#### C\n## D <!-- omit in toc -->\n## E\n# U\n## F");
+        apply_title_order(
+            &mut content,
+            OrderStyle::Decimal,
+            LevelRange { min: 2, max: 3 },
+        );
+        let expected =
+            lines("# T\n## 1 A\n### 1.1 B\n#### C\n## D <!-- omit in toc -->\n## 2 E\n# U\n## 1 F");
+        assert_eq!(expected, content);
+    }
+
+    #[test]
+    fn test_apply_title_order_setext_and_html() {
+        let mut content = lines("1 Title\n=====\n\n2.1 Sub\n---\n<h3>Deep</h3>");
+        apply_title_order(&mut content, OrderStyle::Decimal, LevelRange::default());
+        let expected = lines("1 Title\n=====\n\n1.1 Sub\n---\n<h3>1.1.1 Deep</h3>");
+        assert_eq!(expected, content);
+    }
+
+    #[test]
+    fn test_order_style_format() {
+        assert_eq!("IV. ", OrderStyle::Roman.format(&[4]));
+        assert_eq!("XIV.2 ", OrderStyle::Roman.format(&[14, 2]));
+        assert_eq!("二十一、", OrderStyle::Chinese.format(&[21]));
+        assert_eq!("（十）", OrderStyle::Chinese.format(&[1, 10]));
+        assert_eq!("② ", OrderStyle::Chinese.format(&[1, 1, 1, 1, 2]));
+    }
+}
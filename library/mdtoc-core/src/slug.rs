@@ -0,0 +1,224 @@
+use crate::Error;
+use regex::Regex;
+use std::collections::HashMap;
+use std::str::FromStr;
+use std::sync::OnceLock;
+
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+pub enum SlugStyle {
+    #[default]
+    GitHub,
+    GitLab,
+    Gitea,
+    AzureDevOps,
+    Typora,
+}
+
+impl FromStr for SlugStyle {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "github" => Ok(SlugStyle::GitHub),
+            "gitlab" => Ok(SlugStyle::GitLab),
+            "gitea" => Ok(SlugStyle::Gitea),
+            "azure" | "azure-devops" => Ok(SlugStyle::AzureDevOps),
+            "typora" => Ok(SlugStyle::Typora),
+            _ => Err(Error::UnknownValue {
+                kind: "slug style",
+                value: s.to_owned(),
+                expected: "github, gitlab, gitea, azure, typora",
+            }),
+        }
+    }
+}
+
+impl SlugStyle {
+    pub fn slugify(&self, text: &str) -> String {
+        let text = plain_text(text).to_lowercase();
+        match self {
+            // github-slugger: drop punctuation and symbols, every space becomes a hyphen.
+            SlugStyle::GitHub => text
+                .chars()
+                .filter_map(|c| match c {
+                    ' ' => Some('-'),
+                    '-' | '_' => Some(c),
+                    c if c.is_alphanumeric() => Some(c),
+                    _ => None,
+                })
+                .collect(),
+            // GitLab: drop non-word characters, spaces become hyphens, hyphen runs are squeezed.
+            SlugStyle::GitLab => {
+                let slug: String = text
+                    .chars()
+                    .filter_map(|c| match c {
+                        ' ' => Some('-'),
+                        '-' | '_' => Some(c),
+                        c if c.is_alphanumeric() => Some(c),
+                        _ => None,
+                    })
+                    .collect();
+                squeeze_hyphens(&slug)
+            }
+            // Gitea: trimmed, any whitespace becomes a hyphen, an empty id falls back to `heading`.
+            SlugStyle::Gitea => {
+                let slug: String = text
+                    .trim()
+                    .chars()
+                    .filter_map(|c| match c {
+                        c if c.is_whitespace() => Some('-'),
+                        '-' | '_' => Some(c),
+                        c if c.is_alphanumeric() => Some(c),
+                        _ => None,
+                    })
+                    .collect();
+                if slug.is_empty() {
+                    "heading".to_owned()
+                } else {
+                    slug
+                }
+            }
+            // Azure DevOps: spaces become hyphens, everything else is kept but percent-encoded.
+            SlugStyle::AzureDevOps => text
+                .trim()
+                .replace(' ', "-")
+                .chars()
+                .map(percent_encode)
+                .collect(),
+            // Typora: whitespace runs become one hyphen, only ASCII punctuation is dropped.
+            SlugStyle::Typora => text
+                .split_whitespace()
+                .collect::<Vec<&str>>()
+                .join("-")
+                .chars()
+                .filter(|&c| c == '-' || c == '_' || !c.is_ascii_punctuation())
+                .collect(),
+        }
+    }
+}
+
+pub struct Slugger {
+    style: SlugStyle,
+    occurrences: HashMap<String, usize>,
+}
+
+impl Slugger {
+    pub fn new(style: SlugStyle) -> Self {
+        Slugger {
+            style,
+            occurrences: HashMap::new(),
+        }
+    }
+
+    pub fn slug(&mut self, text: &str) -> String {
+        let original = self.style.slugify(text);
+        match self.style {
+            // GitHub and Gitea keep counting until the suffixed slug does not collide with any
+            // slug generated so far, e.g. `a`, `a-1`, `a` gives `a`, `a-1`, `a-2`.
+            SlugStyle::GitHub | SlugStyle::Gitea => {
+                let mut slug = original.clone();
+                while self.occurrences.contains_key(&slug) {
+                    let count = self.occurrences.entry(original.clone()).or_insert(0);
+                    *count += 1;
+                    slug = format!("{original}-{count}");
+                }
+                self.occurrences.insert(slug.clone(), 0);
+                slug
+            }
+            // The others only count repeats of the same text, e.g. `a`, `a-1`, `a` gives `a`, `a-1`, `a-1`.
+            SlugStyle::GitLab | SlugStyle::AzureDevOps | SlugStyle::Typora => {
+                let count = self.occurrences.entry(original.clone()).or_insert(0);
+                let slug = if *count == 0 {
+                    original
+                } else {
+                    format!("{original}-{count}")
+                };
+                *count += 1;
+                slug
+            }
+        }
+    }
+}
+
+/// Strips inline markdown so that the slug is built from the rendered text.
+pub fn plain_text(text: &str) -> String {
+    static LINK: OnceLock<Regex> = OnceLock::new();
+    static TAG: OnceLock<Regex> = OnceLock::new();
+    static EMPHASIS: OnceLock<Regex> = OnceLock::new();
+    let link = LINK.get_or_init(|| Regex::new(r"!?\[([^\]]*)\]\([^)]*\)").unwrap());
+    let tag = TAG.get_or_init(|| Regex::new(r"</?[A-Za-z][^>]*>").unwrap());
+    let emphasis = EMPHASIS.get_or_init(|| Regex::new(r"\*+|~~|`|\b__|__\b").unwrap());
+    let text = link.replace_all(text, "$1");
+    let text = tag.replace_all(&text, "");
+    emphasis.replace_all(&text, "").into_owned()
+}
+
+fn squeeze_hyphens(text: &str) -> String {
+    let mut squeezed = String::with_capacity(text.len());
+    for c in text.chars() {
+        if c != '-' || !squeezed.ends_with('-') {
+            squeezed.push(c);
+        }
+    }
+    squeezed
+}
+
+fn percent_encode(c: char) -> String {
+    if c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | '.' | '~') {
+        return c.to_string();
+    }
+    let mut buf = [0; 4];
+    c.encode_utf8(&mut buf)
+        .bytes()
+        .map(|byte| format!("%{byte:02X}"))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn slugs(style: SlugStyle, titles: &[&str]) -> Vec<String> {
+        let mut slugger = Slugger::new(style);
+        titles.iter().map(|title| slugger.slug(title)).collect()
+    }
+
+    #[test]
+    fn test_slugify() {
+        let title = "Hello,  World! 🚀 中文、标题 `code` [link](url)";
+        assert_eq!(
+            "hello--world--中文标题-code-link",
+            SlugStyle::GitHub.slugify(title)
+        );
+        assert_eq!(
+            "hello-world-中文标题-code-link",
+            SlugStyle::GitLab.slugify(title)
+        );
+        assert_eq!(
+            "hello--world--中文标题-code-link",
+            SlugStyle::Gitea.slugify(title)
+        );
+        assert_eq!(
+            "hello%2C--world%21-%F0%9F%9A%80-%E4%B8%AD%E6%96%87%E3%80%81%E6%A0%87%E9%A2%98-code-link",
+            SlugStyle::AzureDevOps.slugify(title)
+        );
+        assert_eq!(
+            "hello-world-🚀-中文、标题-code-link",
+            SlugStyle::Typora.slugify(title)
+        );
+        assert_eq!("heading", SlugStyle::Gitea.slugify("🚀"));
+    }
+
+    #[test]
+    fn test_duplicate_slugs() {
+        let titles = ["A", "A 1", "A", "A"];
+        assert_eq!(
+            vec!["a", "a-1", "a-2", "a-3"],
+            slugs(SlugStyle::GitHub, &titles)
+        );
+        assert_eq!(
+            vec!["a", "a-1", "a-1", "a-2"],
+            slugs(SlugStyle::GitLab, &titles)
+        );
+    }
+}
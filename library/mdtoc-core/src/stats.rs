@@ -0,0 +1,192 @@
+use crate::heading::{FenceTracker, Heading};
+use regex::Regex;
+use std::ops::{Add, Range};
+use std::sync::OnceLock;
+
+const WORDS_PER_MINUTE: usize = 200;
+const CJK_PER_MINUTE: usize = 400;
+
+/// Statistics of a section, its subsections included. Headings and fenced code are not counted
+/// as words.
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+pub struct SectionStats {
+    /// Words, counting every CJK character as one.
+    pub words: usize,
+    /// The CJK characters among the words, which are read faster than other words.
+    pub cjk: usize,
+    pub code_blocks: usize,
+    pub images: usize,
+    pub links: usize,
+}
+
+impl Add for SectionStats {
+    type Output = SectionStats;
+
+    fn add(self, other: SectionStats) -> SectionStats {
+        SectionStats {
+            words: self.words + other.words,
+            cjk: self.cjk + other.cjk,
+            code_blocks: self.code_blocks + other.code_blocks,
+            images: self.images + other.images,
+            links: self.links + other.links,
+        }
+    }
+}
+
+impl SectionStats {
+    /// The estimated reading time, at least a minute for a section with any words.
+    pub fn reading_minutes(&self) -> usize {
+        let words = (self.words - self.cjk) as f64 / WORDS_PER_MINUTE as f64;
+        let cjk = self.cjk as f64 / CJK_PER_MINUTE as f64;
+        match self.words {
+            0 => 0,
+            _ => ((words + cjk).ceil() as usize).max(1),
+        }
+    }
+
+    /// Summarizes the length, such as `1.2k words, 5 min`.
+    pub fn summary(&self) -> String {
+        let words = match self.words {
+            1 => "1 word".to_owned(),
+            words if words < 1000 => format!("{words} words"),
+            words if words < 10_000 => format!("{:.1}k words", words as f64 / 1000.0),
+            words => format!("{}k words", words / 1000),
+        };
+        format!("{words}, {} min", self.reading_minutes())
+    }
+}
+
+/// Returns the statistics of the section of every heading.
+pub fn section_stats(lines: &[String], headings: &[Heading]) -> Vec<SectionStats> {
+    let mut counts = vec![SectionStats::default(); lines.len()];
+    let mut fence = FenceTracker::default();
+    for (count, line) in counts.iter_mut().zip(lines) {
+        let opening = !fence.in_block();
+        if fence.is_code(line) {
+            count.code_blocks = usize::from(opening);
+        } else {
+            *count = line_stats(line);
+        }
+    }
+    for heading in headings {
+        counts[heading.line..=heading.end_line()].fill(SectionStats::default());
+    }
+    (0..headings.len())
+        .map(|index| {
+            counts[section_range(headings, index, lines.len())]
+                .iter()
+                .fold(SectionStats::default(), |sum, count| sum + *count)
+        })
+        .collect()
+}
+
+/// Returns the lines of the section of `headings[index]`, which ends before the next heading of
+/// the same or a higher level.
+pub fn section_range(headings: &[Heading], index: usize, len: usize) -> Range<usize> {
+    let heading = &headings[index];
+    let end = headings[index + 1..]
+        .iter()
+        .find(|next| next.level <= heading.level)
+        .map_or(len, |next| next.line);
+    heading.line..end
+}
+
+/// Counts the words of a text: runs of letters and digits, and every CJK character. Returns the
+/// words and the CJK characters among them.
+pub fn count_words(text: &str) -> (usize, usize) {
+    let (mut words, mut cjk) = (0, 0);
+    for token in text.split_whitespace() {
+        let mut in_word = false;
+        for c in token.chars() {
+            if is_cjk(c) {
+                cjk += 1;
+                in_word = false;
+            } else if c.is_alphanumeric() && !in_word {
+                words += 1;
+                in_word = true;
+            }
+        }
+    }
+    (words + cjk, cjk)
+}
+
+fn line_stats(line: &str) -> SectionStats {
+    static LINK: OnceLock<Regex> = OnceLock::new();
+    static TAG: OnceLock<Regex> = OnceLock::new();
+    let link = LINK.get_or_init(|| {
+        Regex::new(r"(!?)\[[^\]]*\][(\[]|<(img)\b|<a\s[^>]*href|<https?://").unwrap()
+    });
+    let tag = TAG.get_or_init(|| Regex::new(r"</?[a-zA-Z][^>]*>").unwrap());
+    let (words, cjk) = count_words(&tag.replace_all(line, " "));
+    let mut stats = SectionStats {
+        words,
+        cjk,
+        ..Default::default()
+    };
+    for caps in link.captures_iter(line) {
+        let image = caps.get(1).is_some_and(|bang| !bang.is_empty()) || caps.get(2).is_some();
+        match image {
+            true => stats.images += 1,
+            false => stats.links += 1,
+        }
+    }
+    stats
+}
+
+/// Han characters, kana and hangul, which are written without spaces between words.
+fn is_cjk(c: char) -> bool {
+    matches!(c as u32,
+        0x3040..=0x30FF | 0x3400..=0x4DBF | 0x4E00..=0x9FFF | 0xAC00..=0xD7A3 | 0xF900..=0xFAFF
+        | 0x20000..=0x2FFFF)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::heading::parse_headings;
+
+    fn lines(content: &str) -> Vec<String> {
+        content.lines().map(str::to_owned).collect()
+    }
+
+    #[test]
+    fn test_count_words() {
+        assert_eq!((4, 0), count_words("Don't split well-known words."));
+        assert_eq!((8, 5), count_words("安装 mdtoc 的方法, see [docs](a.md)"));
+    }
+
+    #[test]
+    fn test_section_stats() {
+        let content = lines(concat!(
+            "# Guide\nIntro text.\n## Install\nRun ![shot](a.png) and [docs](b.md).\n",
+            "```sh\n# not a heading\ncargo install\n```\n## 用法\n读取文件\n<img src=\"c.png\">",
+        ));
+        let headings = parse_headings(&content);
+        let stats = section_stats(&content, &headings);
+        let expected = SectionStats {
+            words: 4,
+            cjk: 0,
+            code_blocks: 1,
+            images: 1,
+            links: 1,
+        };
+        assert_eq!(expected, stats[1]);
+        assert_eq!(
+            (10, 4, 2, 1),
+            (
+                stats[0].words,
+                stats[0].cjk,
+                stats[0].images,
+                stats[0].code_blocks
+            )
+        );
+        assert_eq!("4 words, 1 min", stats[1].summary());
+
+        let long = SectionStats {
+            words: 1234,
+            cjk: 400,
+            ..Default::default()
+        };
+        assert_eq!("1.2k words, 6 min", long.summary());
+    }
+}
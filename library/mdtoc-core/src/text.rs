@@ -0,0 +1,152 @@
+use similar::{capture_diff_slices, Algorithm, DiffOp};
+
+/// The byte order mark, which a decoded text starts with if its file does.
+pub const BOM: char = '\u{feff}';
+
+/// Markdown content split into lines, remembering the line endings, the trailing newline and the
+/// BOM so that the result can be written back exactly as it was read.
+#[derive(Debug, PartialEq)]
+pub struct Text {
+    pub lines: Vec<String>,
+    /// The most common line ending, which new lines are written with.
+    pub eol: &'static str,
+    /// The lines as read with their endings, kept only when the endings are mixed so that the
+    /// lines written back keep theirs.
+    pub endings: Vec<(String, &'static str)>,
+    pub trailing_newline: bool,
+    pub bom: bool,
+}
+
+impl Text {
+    pub fn parse(content: &str) -> Text {
+        let (bom, content) = match content.strip_prefix(BOM) {
+            Some(content) => (true, content),
+            None => (false, content),
+        };
+        let mut endings: Vec<(String, &'static str)> = content
+            .split_inclusive('\n')
+            .map(|line| match line.strip_suffix('\n') {
+                Some(line) => match line.strip_suffix('\r') {
+                    Some(line) => (line.to_owned(), "\r\n"),
+                    None => (line.to_owned(), "\n"),
+                },
+                None => (line.to_owned(), ""),
+            })
+            .collect();
+        let crlf = endings.iter().filter(|(_, eol)| *eol == "\r\n").count();
+        let lf = endings.iter().filter(|(_, eol)| *eol == "\n").count();
+        let eol = if crlf > lf { "\r\n" } else { "\n" };
+        let lines = endings.iter().map(|(line, _)| line.clone()).collect();
+        if crlf == 0 || lf == 0 {
+            endings.clear();
+        }
+        Text {
+            lines,
+            eol,
+            endings,
+            trailing_newline: content.ends_with('\n'),
+            bom,
+        }
+    }
+
+    pub fn render(&self, lines: &[String]) -> String {
+        let eols = self.eols(lines);
+        let mut content = String::new();
+        if self.bom {
+            content.push(BOM);
+        }
+        for (index, (line, eol)) in lines.iter().zip(eols).enumerate() {
+            content.push_str(line);
+            if index + 1 < lines.len() || self.trailing_newline {
+                content.push_str(eol);
+            }
+        }
+        content
+    }
+
+    /// The ending of each line: that of the line it was read as, for a line that is unchanged or
+    /// replaces one, and the most common one for new lines.
+    fn eols(&self, lines: &[String]) -> Vec<&'static str> {
+        let mut eols = vec![self.eol; lines.len()];
+        if self.endings.is_empty() {
+            return eols;
+        }
+        let old: Vec<&str> = self.endings.iter().map(|(line, _)| line.as_str()).collect();
+        let new: Vec<&str> = lines.iter().map(String::as_str).collect();
+        for op in capture_diff_slices(Algorithm::Myers, &old, &new) {
+            let (old_index, old_len, new_index, new_len) = match op {
+                DiffOp::Equal {
+                    old_index,
+                    new_index,
+                    len,
+                } => (old_index, len, new_index, len),
+                DiffOp::Replace {
+                    old_index,
+                    old_len,
+                    new_index,
+                    new_len,
+                } => (old_index, old_len, new_index, new_len),
+                DiffOp::Delete { .. } | DiffOp::Insert { .. } => continue,
+            };
+            for offset in 0..old_len.min(new_len) {
+                let (_, eol) = self.endings[old_index + offset];
+                if !eol.is_empty() {
+                    eols[new_index + offset] = eol;
+                }
+            }
+        }
+        eols
+    }
+}
+
+/// The width in columns, counting East Asian wide characters twice.
+pub fn display_width(text: &str) -> usize {
+    text.chars()
+        .map(|c| match c as u32 {
+            0x1100..=0x115F
+            | 0x2E80..=0xA4CF
+            | 0xAC00..=0xD7A3
+            | 0xF900..=0xFAFF
+            | 0xFE30..=0xFE4F
+            | 0xFF00..=0xFF60
+            | 0xFFE0..=0xFFE6
+            | 0x20000..=0x3FFFD => 2,
+            _ => 1,
+        })
+        .sum()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_round_trip() {
+        for content in [
+            "",
+            "# A",
+            "# A\n",
+            "# A\r\n\r\nb\r\n",
+            "# A\n\n",
+            "\n",
+            "\u{feff}# A\r\n",
+            "# A\r\n\nb\r\nc",
+        ] {
+            let text = Text::parse(content);
+            assert_eq!(content, text.render(&text.lines));
+        }
+        assert_eq!(vec!["# A"], Text::parse("\u{feff}# A").lines);
+    }
+
+    #[test]
+    fn test_render_mixed_line_endings() {
+        let text = Text::parse("# A\r\n\n- old\r\nb\nc\r\n");
+        let lines: Vec<String> = ["# 1 A", "", "- new", "- added", "b", "c"]
+            .map(str::to_owned)
+            .to_vec();
+        assert_eq!(
+            "# 1 A\r\n\n- new\r\n- added\r\nb\nc\r\n",
+            text.render(&lines)
+        );
+    }
+}
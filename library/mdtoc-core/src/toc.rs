@@ -0,0 +1,163 @@
+use crate::format::TocFormat;
+use crate::heading::{Heading, LevelRange};
+use crate::markup::FrontEnd;
+use crate::slug::{SlugStyle, Slugger};
+
+#[derive(Debug, Default, Clone)]
+pub struct TocOptions {
+    pub slug_style: SlugStyle,
+    pub format: TocFormat,
+    pub collapsible: bool,
+    pub levels: LevelRange,
+    pub title: Option<String>,
+    /// Appends the word count and reading time of every section to its entry. Only
+    /// [`Document`](crate::Document) has the section contents to count.
+    pub stats: bool,
+}
+
+/// A heading in the outline tree shared by every TOC format.
+#[derive(Debug, Clone, PartialEq)]
+pub struct TocEntry {
+    pub level: u8,
+    pub text: String,
+    pub slug: String,
+    /// The explicit ID of the heading.
+    pub id: Option<String>,
+    pub line: usize,
+    /// Text rendered after the link, such as the section statistics.
+    pub note: Option<String>,
+    pub children: Vec<TocEntry>,
+}
+
+impl TocEntry {
+    /// The note in parentheses after the link, or nothing without a note.
+    pub fn note_suffix(&self) -> String {
+        self.note
+            .as_ref()
+            .map_or_else(String::new, |note| format!(" ({note})"))
+    }
+}
+
+/// Builds the outline of the listed headings. Slugs are counted over all headings, since the
+/// renderer generates anchors for excluded headings as well.
+pub fn build_outline(
+    headings: &[Heading],
+    slug_style: SlugStyle,
+    levels: LevelRange,
+) -> Vec<TocEntry> {
+    let mut slugger = Slugger::new(slug_style);
+    let entries: Vec<TocEntry> = headings
+        .iter()
+        .map(|heading| (heading, heading.anchor(&mut slugger)))
+        .filter(|(heading, _)| heading.is_listed(levels))
+        .map(|(heading, slug)| TocEntry {
+            level: heading.level,
+            text: heading.text.clone(),
+            slug,
+            id: heading.id.clone(),
+            line: heading.line,
+            note: None,
+            children: Vec::new(),
+        })
+        .collect();
+    let mut entries = entries.into_iter().peekable();
+    nest(&mut entries, 0)
+}
+
+/// Renders the TOC of the headings in the syntax of `front_end`.
+pub fn generate_toc(
+    headings: &[Heading],
+    options: &TocOptions,
+    front_end: &dyn FrontEnd,
+) -> Vec<String> {
+    let outline = build_outline(headings, options.slug_style, options.levels);
+    front_end.render_toc(&outline, options)
+}
+
+fn nest<I>(entries: &mut std::iter::Peekable<I>, parent_level: u8) -> Vec<TocEntry>
+where
+    I: Iterator<Item = TocEntry>,
+{
+    let mut siblings = Vec::new();
+    while let Some(mut entry) = entries.next_if(|entry| entry.level > parent_level) {
+        entry.children = nest(entries, entry.level);
+        siblings.push(entry);
+    }
+    siblings
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::heading::parse_headings;
+    use crate::markup::Markup;
+
+    #[test]
+    fn test_generate_toc() {
+        let content: Vec<String> = "# Title\n## Install Guide\n### On Linux!\n## Usage\n## Usage"
+            .lines()
+            .map(str::to_owned)
+            .collect();
+        let expected = vec![
+            "- [Title](#title)",
+            "  - [Install Guide](#install-guide)",
+            "    - [On Linux!](#on-linux)",
+            "  - [Usage](#usage)",
+            "  - [Usage](#usage-1)",
+        ];
+        assert_eq!(
+            expected,
+            generate_toc(
+                &parse_headings(&content),
+                &TocOptions::default(),
+                Markup::Markdown.front_end()
+            )
+        );
+    }
+
+    #[test]
+    fn test_generate_toc_filtered() {
+        let content: Vec<String> = "# T\n## A\n### B\n#### C\n## A <!-- omit in toc -->\n## A"
+            .lines()
+            .map(str::to_owned)
+            .collect();
+        let options = TocOptions {
+            levels: LevelRange { min: 2, max: 3 },
+            ..Default::default()
+        };
+        let expected = vec!["- [A](#a)", "  - [B](#b)", "- [A](#a-2)"];
+        assert_eq!(
+            expected,
+            generate_toc(
+                &parse_headings(&content),
+                &options,
+                Markup::Markdown.front_end()
+            )
+        );
+    }
+
+    #[test]
+    fn test_build_outline() {
+        let content: Vec<String> = "## A\n#### B\n### C\n# D"
+            .lines()
+            .map(str::to_owned)
+            .collect();
+        let outline = build_outline(
+            &parse_headings(&content),
+            SlugStyle::GitHub,
+            LevelRange::default(),
+        );
+        let shape: Vec<(String, Vec<String>)> = outline
+            .iter()
+            .map(|entry| {
+                let children = entry.children.iter().map(|c| c.slug.clone()).collect();
+                (entry.slug.clone(), children)
+            })
+            .collect();
+        let expected = vec![
+            ("a".to_owned(), vec!["b".to_owned(), "c".to_owned()]),
+            ("d".to_owned(), vec![]),
+        ];
+        assert_eq!(expected, shape);
+    }
+}